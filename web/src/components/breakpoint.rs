@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+/// Tailwind's min-width breakpoints. Styles without one apply at every size, so the smallest
+/// screens are the base and each breakpoint overrides them from its width up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Breakpoint {
+    Sm,
+    Md,
+    Lg,
+}
+
+impl Breakpoint {
+    pub const ALL: [Breakpoint; 3] = [Breakpoint::Sm, Breakpoint::Md, Breakpoint::Lg];
+
+    /// The class prefix, without the colon.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Breakpoint::Sm => "sm",
+            Breakpoint::Md => "md",
+            Breakpoint::Lg => "lg",
+        }
+    }
+
+    /// Screen width in pixels the breakpoint's classes apply from.
+    pub fn min_width(&self) -> u32 {
+        match self {
+            Breakpoint::Sm => 640,
+            Breakpoint::Md => 768,
+            Breakpoint::Lg => 1024,
+        }
+    }
+
+    /// `class` scoped to `breakpoint`, e.g. `md:p-4`; unchanged for `None`.
+    pub fn scope(breakpoint: Option<Breakpoint>, class: &str) -> String {
+        match breakpoint {
+            Some(breakpoint) => format!("{}:{class}", breakpoint.prefix()),
+            None => class.to_string(),
+        }
+    }
+
+    /// Splits a leading `sm:`, `md:` or `lg:` off `class`.
+    pub fn split(class: &str) -> (Option<Breakpoint>, &str) {
+        Breakpoint::ALL
+            .into_iter()
+            .find_map(|breakpoint| {
+                let rest = class.strip_prefix(breakpoint.prefix())?.strip_prefix(':')?;
+                Some((Some(breakpoint), rest))
+            })
+            .unwrap_or((None, class))
+    }
+}
+
+/// Screen sizes the canvas can be previewed at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Device {
+    Desktop,
+    Tablet,
+    Mobile,
+}
+
+impl Device {
+    pub const ALL: [Device; 3] = [Device::Desktop, Device::Tablet, Device::Mobile];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Device::Desktop => "Desktop",
+            Device::Tablet => "Tablet",
+            Device::Mobile => "Mobile",
+        }
+    }
+
+    /// Canvas width in pixels; `None` fills the available space.
+    pub fn width(&self) -> Option<u32> {
+        match self {
+            Device::Desktop => None,
+            Device::Tablet => Some(768),
+            Device::Mobile => Some(375),
+        }
+    }
+
+    /// The widest breakpoint active at this size; `None` when only unprefixed classes apply.
+    pub fn breakpoint(&self) -> Option<Breakpoint> {
+        match self {
+            Device::Desktop => Some(Breakpoint::Lg),
+            Device::Tablet => Some(Breakpoint::Md),
+            Device::Mobile => None,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Device::Desktop => "M9.75 17L9 20l-1 1h8l-1-1-.75-3M3 13h18M5 17h14a2 2 0 002-2V5a2 2 0 00-2-2H5a2 2 0 00-2 2v10a2 2 0 002 2z",
+            Device::Tablet => "M12 18h.01M7 21h10a2 2 0 002-2V5a2 2 0 00-2-2H7a2 2 0 00-2 2v14a2 2 0 002 2z",
+            Device::Mobile => "M12 18h.01M8 21h8a2 2 0 002-2V5a2 2 0 00-2-2H8a2 2 0 00-2 2v14a2 2 0 002 2z",
+        }
+    }
+}
+
+/// The classes in `classes` as they apply at `breakpoint`, with no breakpoint prefixes left.
+///
+/// Tailwind's breakpoints follow the browser window, not the canvas, so the canvas previews a
+/// narrower screen by applying the matching prefixed classes directly. A prefixed class
+/// replaces earlier classes for the same property (`md:p-8` drops `p-4`); classes for wider
+/// breakpoints are left out.
+pub fn resolve_classes(classes: &str, breakpoint: Option<Breakpoint>) -> String {
+    let sizes = std::iter::once(None).chain(Breakpoint::ALL.map(Some));
+    let mut resolved: Vec<&str> = Vec::new();
+    for size in sizes.take_while(|&size| size <= breakpoint) {
+        for (class_size, class) in classes.split_whitespace().map(Breakpoint::split) {
+            if class_size != size {
+                continue;
+            }
+            if size.is_some() {
+                resolved.retain(|existing| utility_group(existing) != utility_group(class));
+            }
+            resolved.push(class);
+        }
+    }
+    resolved.join(" ")
+}
+
+/// Tailwind's color names, so `bg-white` and `bg-blue-600` count as the same property.
+const COLOR_NAMES: [&str; 27] = [
+    "slate", "gray", "zinc", "neutral", "stone", "red", "orange", "amber", "yellow", "lime",
+    "green", "emerald", "teal", "cyan", "sky", "blue", "indigo", "violet", "purple", "fuchsia",
+    "pink", "rose", "white", "black", "transparent", "current", "inherit",
+];
+
+/// Roughly the CSS property a class sets, e.g. `p-4` and `p-8` both give `p`, `bg-white` and
+/// `bg-blue-600` both give `bg-color`. Variants like `hover:` are kept, so they don't clash
+/// with the plain class.
+fn utility_group(class: &str) -> String {
+    let (variants, utility) = class.rsplit_once(':').unwrap_or(("", class));
+    // Arbitrary values like `bg-[#1e40af]` or `text-[var(--theme-text)]`
+    if let Some((property, value)) = utility.split_once("-[") {
+        let is_color = ["#", "rgb", "hsl", "var(--theme-"].iter().any(|start| value.starts_with(start));
+        return if is_color { format!("{variants}:{property}-color") } else { format!("{variants}:{property}") };
+    }
+    let mut parts: Vec<&str> = utility.split('-').collect();
+    let is_value = |part: &str| {
+        part.parse::<f32>().is_ok()
+            || part.contains('/')
+            || part.starts_with('[')
+            || matches!(part, "px" | "auto" | "full" | "screen" | "none" | "xs" | "sm" | "base" | "md" | "lg" | "xl" | "2xl" | "3xl" | "4xl" | "5xl" | "6xl")
+    };
+    if parts.len() > 1 && parts.last().is_some_and(|part| is_value(part)) {
+        parts.pop();
+    }
+    if parts.len() > 1 && parts.last().is_some_and(|part| COLOR_NAMES.contains(part)) {
+        parts.pop();
+        parts.push("color");
+    } else if parts.len() > 1 && parts.last().is_some_and(|part| matches!(*part, "left" | "center" | "right" | "justify")) {
+        parts.pop();
+        parts.push("align");
+    }
+    format!("{variants}:{}", parts.join("-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixed_classes_override_smaller_sizes() {
+        let classes = "p-4 text-gray-700 text-sm hover:bg-blue-600 md:p-8 md:text-blue-500 lg:hidden";
+        assert_eq!(resolve_classes("bg-[var(--theme-button)] md:bg-red-500 md:w-[120px]", Some(Breakpoint::Md)), "bg-red-500 w-[120px]");
+
+        assert_eq!(resolve_classes(classes, None), "p-4 text-gray-700 text-sm hover:bg-blue-600");
+        assert_eq!(resolve_classes(classes, Some(Breakpoint::Md)), "text-sm hover:bg-blue-600 p-8 text-blue-500");
+        assert_eq!(resolve_classes(classes, Some(Breakpoint::Lg)), "text-sm hover:bg-blue-600 p-8 text-blue-500 hidden");
+    }
+}
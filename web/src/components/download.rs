@@ -0,0 +1,87 @@
+//! Saves generated files for the user: a browser download on the web, a native save dialog on
+//! desktop.
+
+use dioxus::prelude::*;
+
+use super::export::ExportedFile;
+
+/// Saves `contents` as `file_name`. Returns `Ok(false)` if the user cancelled the save dialog.
+#[cfg(not(all(feature = "desktop", not(feature = "web"))))]
+pub async fn download_file(file_name: &str, mime_type: &str, contents: &str) -> Result<bool, String> {
+    let eval = document::eval(
+        r#"
+        const [fileName, mimeType, contents] = await dioxus.recv();
+        const url = URL.createObjectURL(new Blob([contents], { type: mimeType }));
+        const link = document.createElement("a");
+        link.href = url;
+        link.download = fileName;
+        link.click();
+        // Revoked once the click has been handled, or the download can find the URL gone
+        setTimeout(() => URL.revokeObjectURL(url), 0);
+        "#,
+    );
+    eval.send((file_name, mime_type, contents))
+        .map_err(|err| format!("Could not download {file_name}: {err:?}"))?;
+    Ok(true)
+}
+
+/// Saves `contents` as `file_name`. Returns `Ok(false)` if the user cancelled the save dialog.
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+pub async fn download_file(file_name: &str, _mime_type: &str, contents: &str) -> Result<bool, String> {
+    let mut dialog = rfd::AsyncFileDialog::new().set_file_name(file_name);
+    if let Some((_, extension)) = file_name.rsplit_once('.') {
+        dialog = dialog.add_filter(extension.to_uppercase(), &[extension]);
+    }
+    let Some(file) = dialog.save_file().await else {
+        return Ok(false);
+    };
+    file.write(contents.as_bytes())
+        .await
+        .map_err(|err| format!("Could not save {file_name}: {err}"))?;
+    Ok(true)
+}
+
+/// Saves several files at once: one browser download each on the web, a single folder picker
+/// on desktop. Returns `Ok(false)` if the user cancelled.
+#[cfg(not(all(feature = "desktop", not(feature = "web"))))]
+pub async fn download_files(files: &[ExportedFile]) -> Result<bool, String> {
+    for file in files {
+        download_file(&file.file_name, file.mime_type, &file.contents).await?;
+    }
+    Ok(true)
+}
+
+/// Saves several files at once: one browser download each on the web, a single folder picker
+/// on desktop. Returns `Ok(false)` if the user cancelled.
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+pub async fn download_files(files: &[ExportedFile]) -> Result<bool, String> {
+    let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await else {
+        return Ok(false);
+    };
+    for file in files {
+        std::fs::write(folder.path().join(&file.file_name), &file.contents)
+            .map_err(|err| format!("Could not save {}: {err}", file.file_name))?;
+    }
+    Ok(true)
+}
+
+/// Puts `text` on the system clipboard, failing if the browser refuses it.
+pub async fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut eval = document::eval(
+        r#"
+        const text = await dioxus.recv();
+        try {
+            await navigator.clipboard.writeText(text);
+            dioxus.send(null);
+        } catch (err) {
+            dioxus.send(String(err));
+        }
+        "#,
+    );
+    eval.send(text).map_err(|err| format!("Could not copy to the clipboard: {err:?}"))?;
+    match eval.recv::<Option<String>>().await {
+        Ok(None) => Ok(()),
+        Ok(Some(err)) => Err(format!("Could not copy to the clipboard: {err}")),
+        Err(err) => Err(format!("Could not copy to the clipboard: {err:?}")),
+    }
+}
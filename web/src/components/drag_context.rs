@@ -1,6 +1,14 @@
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::breakpoint::{Breakpoint, Device};
+use super::export::{ExportFormat, ExportOptions, ExportedFile};
+use super::grid::{GridSettings, MAX_COLUMNS};
+use super::history::{Command, History, Patch};
+use super::theme::{ColorScheme, ThemeSettings};
+use super::persistence::{self, SaveStatus};
+use super::project_file::unique_slug;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DraggableComponent {
     pub id: String,
@@ -10,10 +18,37 @@ pub struct DraggableComponent {
     pub in_drop_zone: bool,
     pub properties: ComponentProperties,
     pub parent_id: Option<String>,
-    pub children: Vec<String>,
+    /// One slot per column for layout components; empty for content components.
+    #[serde(default)]
+    pub columns: Vec<ColumnSlot>,
+    /// Track counts and gap for layout components; `None` for content components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<GridSettings>,
+    /// Left off the canvas (and optionally the export) but kept in the document.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Can't be moved, deleted or edited until unlocked. A locked layout's columns keep their
+    /// children, and a locked component keeps the layout it is in.
+    #[serde(default)]
+    pub locked: bool,
 }
 
+/// A column of a layout component, holding its children's IDs in display order.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnSlot {
+    pub children: Vec<String>,
+    /// Grid tracks the column stretches across.
+    pub span: usize,
+}
+
+impl Default for ColumnSlot {
+    fn default() -> Self {
+        Self { children: Vec::new(), span: 1 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum ComponentType {
     Header,
     Hero,
@@ -23,28 +58,52 @@ pub enum ComponentType {
     Form,
     Card,
     Footer,
-    OneColumn,
-    TwoColumns,
-    ThreeColumns,
-    FourColumns,
-    FiveColumns,
+    Grid,
     Custom(String),
+    /// A tagged type this version does not know, e.g. `{ "Chart": { ... } }`, kept exactly as
+    /// it was read so saving the project again doesn't lose it.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct ComponentProperties {
     pub styles: Vec<String>,
     pub content: Option<String>,
     pub attributes: std::collections::HashMap<String, String>,
+    /// Library asset an Image component shows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_id: Option<String>,
 }
 
-impl Default for ComponentProperties {
-    fn default() -> Self {
-        Self {
-            styles: vec![],
-            content: None,
-            attributes: std::collections::HashMap::new(),
-        }
+/// Unknown type names (from a newer version, or a hand-edited file) load as `Custom` instead
+/// of failing, so the component survives a round trip.
+impl<'de> Deserialize<'de> for ComponentType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let name = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(name) => name,
+            serde_json::Value::Object(map) if map.len() == 1 => {
+                return match map.get("Custom") {
+                    Some(serde_json::Value::String(name)) => Ok(ComponentType::Custom(name.clone())),
+                    _ => Ok(ComponentType::Unknown(serde_json::Value::Object(map))),
+                };
+            }
+            other => return Err(D::Error::custom(format!("invalid component type: {other}"))),
+        };
+        Ok(match name.as_str() {
+            "Header" => ComponentType::Header,
+            "Hero" => ComponentType::Hero,
+            "Text" => ComponentType::Text,
+            "Image" => ComponentType::Image,
+            "Button" => ComponentType::Button,
+            "Form" => ComponentType::Form,
+            "Card" => ComponentType::Card,
+            "Footer" => ComponentType::Footer,
+            "Grid" => ComponentType::Grid,
+            _ => ComponentType::Custom(name),
+        })
     }
 }
 
@@ -59,13 +118,9 @@ impl ComponentType {
             ComponentType::Form => "M11 16l-4-4m0 0l4-4m-4 4h14m-5 4v1a3 3 0 01-3 3H6a3 3 0 01-3-3V7a3 3 0 013-3h7a3 3 0 013 3v1",
             ComponentType::Card => "M4 6a2 2 0 012-2h2a2 2 0 012 2v2a2 2 0 01-2 2H6a2 2 0 01-2-2V6zM14 6a2 2 0 012-2h2a2 2 0 012 2v2a2 2 0 01-2 2h-2a2 2 0 01-2-2V6z",
             ComponentType::Footer => "M4 6h16M4 18h16",
-            ComponentType::OneColumn => "M4 6h16M4 18h16",
-            ComponentType::TwoColumns => "M4 6h16M4 18h16M10 6v12",
-            ComponentType::ThreeColumns => "M4 6h16M4 18h16M10 6v12M14 6v12",
-            ComponentType::FourColumns => "M4 6h16M4 18h16M10 6v12M14 6v12M18 6v12",
-            ComponentType::FiveColumns => "M4 6h16M4 18h16M10 6v12M14 6v12M18 6v12M22 6v12",
+            ComponentType::Grid => "M4 6h16M4 18h16M10 6v12M14 6v12",
 
-            ComponentType::Custom(_) => "M12 6V4m0 2a2 2 0 100 4m0-4a2 2 0 110 4m-6 8a2 2 0 100-4m0 4a2 2 0 100 4m0-4v2m0-6V4m6 6v10m6-2a2 2 0 100-4m0 4a2 2 0 100 4m0-4v2m0-6V4",
+            ComponentType::Custom(_) | ComponentType::Unknown(_) => "M12 6V4m0 2a2 2 0 100 4m0-4a2 2 0 110 4m-6 8a2 2 0 100-4m0 4a2 2 0 100 4m0-4v2m0-6V4m6 6v10m6-2a2 2 0 100-4m0 4a2 2 0 100 4m0-4v2m0-6V4",
         }
     }
 
@@ -79,328 +134,1379 @@ impl ComponentType {
             ComponentType::Form => "Form Component",
             ComponentType::Card => "Card Component",
             ComponentType::Footer => "Footer Component",
-            ComponentType::OneColumn => "One Column Layout",
-            ComponentType::TwoColumns => "Two Column Layout",
-            ComponentType::ThreeColumns => "Three Column Layout",
-            ComponentType::FourColumns => "Four Column Layout",
-            ComponentType::FiveColumns => "Five Column Layout",
+            ComponentType::Grid => "Grid Layout",
             ComponentType::Custom(name) => name,
+            ComponentType::Unknown(value) => {
+                value.as_object().and_then(|map| map.keys().next()).map_or("Unknown Component", String::as_str)
+            }
+        }
+    }
+
+    /// Layouts go on the canvas or into other layouts' columns; everything else goes into a
+    /// layout's columns.
+    pub fn is_layout(&self) -> bool {
+        matches!(self, ComponentType::Grid)
+    }
+
+    /// Placeholder for the secondary text slot, for component types that render one under
+    /// their name. `ComponentProperties::content` replaces it when set.
+    pub fn default_body(&self) -> Option<&'static str> {
+        match self {
+            ComponentType::Hero => Some("This is a hero section"),
+            ComponentType::Card => Some("Card content goes here"),
+            _ => None,
+        }
+    }
+}
+
+impl DraggableComponent {
+    /// A fresh, unplaced component of `component_type`.
+    pub fn new(id: String, component_type: ComponentType) -> Self {
+        Self {
+            id,
+            name: component_type.default_content().to_string(),
+            position: (20.0, 20.0),
+            in_drop_zone: false,
+            properties: ComponentProperties::default(),
+            parent_id: None,
+            columns: if component_type.is_layout() { vec![ColumnSlot::default(); 2] } else { Vec::new() },
+            grid: component_type.is_layout().then(GridSettings::default),
+            hidden: false,
+            locked: false,
+            component_type,
+        }
+    }
+
+    /// The secondary text to render: the user's content, or the type's placeholder.
+    pub fn body(&self) -> Option<&str> {
+        self.properties
+            .content
+            .as_deref()
+            .or_else(|| self.component_type.default_body())
+    }
+
+    /// `base` followed by the user's Tailwind classes.
+    pub fn class_list(&self, base: &str) -> String {
+        let mut classes = base.to_string();
+        for style in &self.properties.styles {
+            classes.push(' ');
+            classes.push_str(style);
         }
+        classes
+    }
+
+    /// Classes for a layout's grid container, before the user's own.
+    pub fn grid_class(&self) -> String {
+        self.grid.clone().unwrap_or_default().class(self.columns.len())
+    }
+
+    /// Span class of column `column_index` of a layout, if it spans more than one track.
+    pub fn column_span_class(&self, column_index: usize) -> Option<&'static str> {
+        let span = self.columns.get(column_index)?.span;
+        self.grid.clone().unwrap_or_default().span_class(span, self.columns.len())
+    }
+
+    /// User attributes sorted by name, so exports are stable.
+    pub fn sorted_attributes(&self) -> Vec<(&String, &String)> {
+        let mut attributes: Vec<_> = self.properties.attributes.iter().collect();
+        attributes.sort();
+        attributes
     }
 }
 
-// Global context provider
-static mut COMPONENTS: GlobalSignal<Vec<DraggableComponent>> = Signal::global(Vec::new);
-static mut CURRENTLY_DRAGGING: GlobalSignal<Option<String>> = Signal::global(|| None);
-static mut NEXT_ID: GlobalSignal<usize> = Signal::global(|| 1);
+/// A file uploaded to the project's asset library.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub id: String,
+    pub name: String,
+    pub file_type: String,
+    pub preview_url: String,
+    pub size: String,
+}
 
-#[derive(Clone, PartialEq)]
-pub struct DragDropContext;
+impl Asset {
+    /// Whether the asset is an image with a preview to show.
+    pub fn is_image(&self) -> bool {
+        self.file_type.starts_with("image/") && !self.preview_url.is_empty()
+    }
 
-impl DragDropContext {
+    /// Where exports expect the file, relative to the page: `assets/` plus the file name, with
+    /// anything that isn't safe in a URL replaced by `-`.
+    pub fn export_path(&self) -> String {
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '-' })
+            .collect();
+        format!("assets/{file_name}")
+    }
+
+    /// Short type name for display: `PNG` for `image/png`, `SVG` for `image/svg+xml`.
+    pub fn type_label(&self) -> String {
+        let subtype = self.file_type.rsplit('/').next().unwrap_or_default();
+        let subtype = subtype.split('+').next().unwrap_or_default();
+        match subtype {
+            "jpeg" => "JPG".to_string(),
+            subtype => subtype.to_uppercase(),
+        }
+    }
+}
+
+/// A page of the project, with its own component tree.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Page {
+    pub id: String,
+    pub name: String,
+    /// URL path of the page, e.g. `about` for `/about`. Empty for the home page.
+    pub slug: String,
+    pub components: Vec<DraggableComponent>,
+}
+
+impl Page {
+    /// Name of the page's exported file, without extension.
+    pub fn file_stem(&self) -> &str {
+        if self.slug.is_empty() {
+            "index"
+        } else {
+            &self.slug
+        }
+    }
+}
+
+/// The builder document: the project's pages and which one is open, the counter used to hand
+/// out component and page ids, the theme and the asset library. All mutations live here so
+/// the logic can be used (and tested) without a Dioxus runtime.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// Never empty.
+    pub pages: Vec<Page>,
+    /// Page shown on the canvas; component edits apply to this page.
+    pub active_page: String,
+    pub next_id: usize,
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            pages: vec![Page {
+                id: "page_1".into(),
+                name: "Home".into(),
+                slug: String::new(),
+                components: Vec::new(),
+            }],
+            active_page: "page_1".into(),
+            next_id: 2,
+            theme: ThemeSettings::default(),
+            assets: Vec::new(),
+        }
+    }
+}
+
+impl Document {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
-    pub fn add_component(&self, component_type: ComponentType) -> String {
-        unsafe {
-            let id = format!("component_{}", NEXT_ID());
-            NEXT_ID.set(NEXT_ID() + 1);
-            
-            let component = DraggableComponent {
-                id: id.clone(),
-                name: component_type.default_content().to_string(),
-                component_type,
-                position: (20.0, 20.0),
-                in_drop_zone: false,
-                properties: ComponentProperties::default(),
-                parent_id: None,
-                children: Vec::new(),
+    /// A single column layout with a welcome header and some placeholder text, shown to
+    /// first-time users.
+    pub fn starter() -> Self {
+        let mut document = Self::new();
+
+        let layout_id = document.add_component(ComponentType::Grid);
+        document.set_grid_column_count(&layout_id, 1);
+        document.move_to_drop_zone(&layout_id, (20.0, 20.0));
+
+        let header_id = document.add_component(ComponentType::Header);
+        document.update_component_name(&header_id, "Welcome");
+        document.add_to_column(&header_id, &layout_id, 0);
+
+        let text_id = document.add_component(ComponentType::Text);
+        document.update_component_name(&text_id, "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris.");
+        document.add_to_column(&text_id, &layout_id, 0);
+
+        document
+    }
+
+    /// The page on the canvas, falling back to the first page if `active_page` is stale.
+    pub fn page(&self) -> &Page {
+        self.pages
+            .iter()
+            .find(|page| page.id == self.active_page)
+            .unwrap_or(&self.pages[0])
+    }
+
+    fn page_mut(&mut self) -> &mut Page {
+        let index = self.pages.iter().position(|page| page.id == self.active_page).unwrap_or(0);
+        &mut self.pages[index]
+    }
+
+    fn get_page_mut(&mut self, page_id: &str) -> Option<&mut Page> {
+        self.pages.iter_mut().find(|page| page.id == page_id)
+    }
+
+    /// `base` turned into a slug that no other page uses, e.g. `about-2`.
+    fn unique_slug(&self, base: &str, page_id: &str) -> String {
+        unique_slug(base, |slug| self.pages.iter().any(|page| page.id != page_id && page.slug == slug))
+    }
+
+    fn next_page_id(&mut self) -> String {
+        let id = format!("page_{}", self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Adds an empty page after the others and opens it.
+    pub fn add_page(&mut self, name: &str) -> String {
+        let id = self.next_page_id();
+        let slug = self.unique_slug(name, &id);
+        self.pages.push(Page {
+            id: id.clone(),
+            name: name.to_string(),
+            slug,
+            components: Vec::new(),
+        });
+        self.active_page = id.clone();
+        id
+    }
+
+    pub fn select_page(&mut self, page_id: &str) {
+        if self.pages.iter().any(|page| page.id == page_id) {
+            self.active_page = page_id.to_string();
+        }
+    }
+
+    pub fn rename_page(&mut self, page_id: &str, name: &str) {
+        if let Some(page) = self.get_page_mut(page_id) {
+            page.name = name.to_string();
+        }
+    }
+
+    /// Sets the page's URL slug, made URL-safe and unique among the pages.
+    pub fn set_page_slug(&mut self, page_id: &str, slug: &str) {
+        let slug = self.unique_slug(slug, page_id);
+        if let Some(page) = self.get_page_mut(page_id) {
+            page.slug = slug;
+        }
+    }
+
+    /// Copies the page and its components (with fresh ids) right after it, and opens the copy.
+    pub fn duplicate_page(&mut self, page_id: &str) -> Option<String> {
+        let index = self.pages.iter().position(|page| page.id == page_id)?;
+        let mut copy = self.pages[index].clone();
+
+        let new_ids: std::collections::HashMap<String, String> = copy
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, component)| (component.id.clone(), format!("component_{}", self.next_id + i)))
+            .collect();
+        self.next_id += copy.components.len();
+        let rename = |id: &mut String| {
+            if let Some(new_id) = new_ids.get(id) {
+                *id = new_id.clone();
+            }
+        };
+        for component in copy.components.iter_mut() {
+            rename(&mut component.id);
+            if let Some(parent_id) = component.parent_id.as_mut() {
+                rename(parent_id);
+            }
+            for slot in component.columns.iter_mut() {
+                slot.children.iter_mut().for_each(rename);
+            }
+        }
+
+        copy.id = self.next_page_id();
+        copy.name = format!("{} (copy)", copy.name);
+        copy.slug = self.unique_slug(&copy.name, &copy.id);
+        self.active_page = copy.id.clone();
+        self.pages.insert(index + 1, copy);
+        Some(self.active_page.clone())
+    }
+
+    /// Moves the page to position `index` in the page list.
+    pub fn move_page(&mut self, page_id: &str, index: usize) {
+        if let Some(current) = self.pages.iter().position(|page| page.id == page_id) {
+            let page = self.pages.remove(current);
+            self.pages.insert(index.min(self.pages.len()), page);
+        }
+    }
+
+    /// Deletes the page, unless it is the last one. Opens a neighbour if it was open.
+    pub fn remove_page(&mut self, page_id: &str) {
+        if self.pages.len() <= 1 {
+            return;
+        }
+        if let Some(index) = self.pages.iter().position(|page| page.id == page_id) {
+            self.pages.remove(index);
+            if self.active_page == page_id {
+                self.active_page = self.pages[index.saturating_sub(1)].id.clone();
+            }
+        }
+    }
+
+    pub fn add_component(&mut self, component_type: ComponentType) -> String {
+        let id = format!("component_{}", self.next_id);
+        self.next_id += 1;
+
+        self.page_mut().components.push(DraggableComponent::new(id.clone(), component_type));
+        id
+    }
+
+    pub fn get_component(&self, component_id: &str) -> Option<&DraggableComponent> {
+        self.page().components.iter().find(|c| c.id == component_id)
+    }
+
+    fn get_component_mut(&mut self, component_id: &str) -> Option<&mut DraggableComponent> {
+        self.page_mut().components.iter_mut().find(|c| c.id == component_id)
+    }
+
+    /// Like [`Self::get_component_mut`], but `None` for locked components.
+    fn get_unlocked_component_mut(&mut self, component_id: &str) -> Option<&mut DraggableComponent> {
+        self.get_component_mut(component_id).filter(|c| !c.locked)
+    }
+
+    fn is_locked(&self, component_id: &str) -> bool {
+        self.get_component(component_id).is_some_and(|c| c.locked)
+    }
+
+    /// Whether `component_id` can leave the place it is in: it isn't locked, and neither is the
+    /// layout holding it.
+    fn can_move(&self, component_id: &str) -> bool {
+        let Some(component) = self.get_component(component_id) else {
+            return false;
+        };
+        !component.locked && !component.parent_id.as_deref().is_some_and(|parent_id| self.is_locked(parent_id))
+    }
+
+    /// Whether `layout_id` is a layout whose columns can take new children, which a locked
+    /// layout can't.
+    pub fn accepts_children(&self, layout_id: &str) -> bool {
+        self.get_component(layout_id).is_some_and(|c| c.component_type.is_layout() && !c.locked)
+    }
+
+    /// Places `component_id` on the canvas at the top level, taking it out of any column.
+    pub fn move_to_drop_zone(&mut self, component_id: &str, position: (f64, f64)) {
+        if !self.can_move(component_id) {
+            return;
+        }
+        self.detach_from_parent(component_id);
+        if let Some(component) = self.get_component_mut(component_id) {
+            component.position = position;
+            component.in_drop_zone = true;
+        }
+    }
+
+    pub fn remove_component(&mut self, component_id: &str) {
+        if !self.can_move(component_id) {
+            return;
+        }
+        // Everything nested inside a layout goes with it, so a locked descendant keeps it
+        let nested: Vec<&DraggableComponent> =
+            self.page().components.iter().filter(|c| self.is_within(&c.id, component_id)).collect();
+        if nested.iter().any(|c| c.locked) {
+            return;
+        }
+        let removed: std::collections::HashSet<String> = nested.into_iter().map(|c| c.id.clone()).collect();
+        self.detach_from_parent(component_id);
+        self.page_mut().components.retain(|c| !removed.contains(&c.id));
+    }
+
+    /// Whether `component_id` is `ancestor_id` itself or nested somewhere inside it.
+    pub fn is_within(&self, component_id: &str, ancestor_id: &str) -> bool {
+        let mut current = Some(component_id.to_string());
+        // Bounded, so a damaged file with a parent cycle can't hang the editor
+        for _ in 0..=self.page().components.len() {
+            let Some(id) = current else {
+                return false;
             };
+            if id == ancestor_id {
+                return true;
+            }
+            current = self.get_component(&id).and_then(|c| c.parent_id.clone());
+        }
+        false
+    }
 
-            COMPONENTS.with_mut(|components| {
-                components.push(component);
-            });
+    pub fn update_component_name(&mut self, component_id: &str, new_name: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            component.name = new_name.to_string();
+        }
+    }
 
-            id
+    pub fn update_component_content(&mut self, component_id: &str, content: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            component.properties.content = (!content.is_empty()).then(|| content.to_string());
         }
     }
 
-    pub fn move_to_drop_zone(&self, component_id: &str, position: (f64, f64)) {
-        unsafe {
-            COMPONENTS.with_mut(|components| {
-                if let Some(component) = components.iter_mut().find(|c| c.id == component_id) {
-                    component.position = position;
-                    component.in_drop_zone = true;
+    /// Adds each whitespace-separated class in `classes` that the component doesn't have yet.
+    pub fn add_component_styles(&mut self, component_id: &str, classes: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            for class in classes.split_whitespace() {
+                if !component.properties.styles.iter().any(|style| style == class) {
+                    component.properties.styles.push(class.to_string());
                 }
-            });
+            }
         }
     }
 
-    pub fn update_position(&self, component_id: &str, position: (f64, f64)) {
-        unsafe {
-            COMPONENTS.with_mut(|components| {
-                if let Some(component) = components.iter_mut().find(|c| c.id == component_id) {
-                    component.position = position;
-                }
-            });
+    pub fn remove_component_style(&mut self, component_id: &str, class: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            component.properties.styles.retain(|style| style != class);
         }
     }
 
-    pub fn remove_component(&self, component_id: &str) {
-        unsafe {
-            COMPONENTS.with_mut(|components| {
-                components.retain(|c| c.id != component_id);
-            });
+    pub fn set_component_attribute(&mut self, component_id: &str, key: &str, value: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            component
+                .properties
+                .attributes
+                .insert(key.to_string(), value.to_string());
         }
     }
 
-    pub fn update_component_name(&self, component_id: &str, new_name: &str) {
-        unsafe {
-            COMPONENTS.with_mut(|components| {
-                if let Some(component) = components.iter_mut().find(|c| c.id == component_id) {
-                    component.name = new_name.to_string();
-                }
-            });
+    pub fn remove_component_attribute(&mut self, component_id: &str, key: &str) {
+        if let Some(component) = self.get_unlocked_component_mut(component_id) {
+            component.properties.attributes.remove(key);
         }
     }
 
-    pub fn get_components_in_palette(&self) -> Vec<DraggableComponent> {
-        unsafe {
-            COMPONENTS().into_iter().filter(|c| !c.in_drop_zone).collect()
+    /// Adds `asset`, or refreshes the entry already there with the same id.
+    pub fn add_asset(&mut self, asset: Asset) {
+        match self.assets.iter_mut().find(|existing| existing.id == asset.id) {
+            Some(existing) => *existing = asset,
+            None => self.assets.push(asset),
+        }
+    }
+
+    pub fn rename_asset(&mut self, asset_id: &str, name: &str) {
+        if let Some(asset) = self.assets.iter_mut().find(|asset| asset.id == asset_id) {
+            asset.name = name.to_string();
+        }
+    }
+
+    /// Removes the asset. Images that showed it go back to their placeholder.
+    pub fn remove_asset(&mut self, asset_id: &str) {
+        self.assets.retain(|asset| asset.id != asset_id);
+        for component in self.pages.iter_mut().flat_map(|page| page.components.iter_mut()) {
+            if component.properties.asset_id.as_deref() == Some(asset_id) {
+                component.properties.asset_id = None;
+            }
         }
     }
 
+    /// Names of the pages with a component showing the asset.
+    pub fn pages_using_asset(&self, asset_id: &str) -> Vec<String> {
+        self.pages
+            .iter()
+            .filter(|page| page.components.iter().any(|c| c.properties.asset_id.as_deref() == Some(asset_id)))
+            .map(|page| page.name.clone())
+            .collect()
+    }
+
+    pub fn get_asset(&self, asset_id: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.id == asset_id)
+    }
+
+    /// Points an Image component at `asset_id`, or back to the placeholder with `None`.
+    pub fn set_component_asset(&mut self, component_id: &str, asset_id: Option<&str>) {
+        if let Some(component) = self
+            .get_unlocked_component_mut(component_id)
+            .filter(|c| c.component_type == ComponentType::Image)
+        {
+            component.properties.asset_id = asset_id.map(str::to_string);
+        }
+    }
+
+    /// Creates an Image component showing `asset_id`, named after the asset, and places it at
+    /// `index` in column `column_index` of `layout_id`. Returns the new component's id.
+    pub fn insert_image_into_column(&mut self, asset_id: &str, layout_id: &str, column_index: usize, index: usize) -> Option<String> {
+        let has_column = self.get_component(layout_id).is_some_and(|layout| column_index < layout.columns.len());
+        if !has_column || !self.accepts_children(layout_id) {
+            return None;
+        }
+        let name = self.get_asset(asset_id).map(|asset| asset.name.clone());
+        let component_id = self.add_component(ComponentType::Image);
+        if let Some(name) = name {
+            self.update_component_name(&component_id, &name);
+        }
+        self.set_component_asset(&component_id, Some(asset_id));
+        self.insert_into_column(&component_id, layout_id, column_index, index);
+        Some(component_id)
+    }
+
+    pub fn get_components_in_palette(&self) -> Vec<DraggableComponent> {
+        self.page().components.iter().filter(|c| !c.in_drop_zone).cloned().collect()
+    }
+
     pub fn get_components_in_drop_zone(&self) -> Vec<DraggableComponent> {
-        unsafe {
-            COMPONENTS().into_iter().filter(|c| c.in_drop_zone).collect()
+        self.page().components.iter().filter(|c| c.in_drop_zone).cloned().collect()
+    }
+
+    /// Appends `component_id` to the end of column `column_index` of the layout `column_id`.
+    pub fn add_to_column(&mut self, component_id: &str, column_id: &str, column_index: usize) {
+        let index = self.get_column_children(column_id, column_index).len();
+        self.insert_into_column(component_id, column_id, column_index, index);
+    }
+
+    /// Places `component_id` at `index` among the children of column `column_index` of the
+    /// layout `column_id`, detaching it from its previous parent first. `index` counts the
+    /// column's children as they are before the move. A layout can't be placed inside itself
+    /// or any of its descendants, and locked components and layouts stay as they are.
+    pub fn insert_into_column(&mut self, component_id: &str, column_id: &str, column_index: usize, index: usize) {
+        if self.is_within(column_id, component_id) || !self.can_move(component_id) || !self.accepts_children(column_id) {
+            return;
+        }
+        let Some(slot) = self.get_component(column_id).and_then(|layout| layout.columns.get(column_index)) else {
+            return;
+        };
+
+        // Moving down within the same column frees up the slot above the target
+        let mut index = index.min(slot.children.len());
+        if slot.children.iter().position(|child| child == component_id).is_some_and(|current| current < index) {
+            index -= 1;
+        }
+
+        self.detach_from_parent(component_id);
+        if let Some(component) = self.get_component_mut(component_id) {
+            component.parent_id = Some(column_id.to_string());
+            component.in_drop_zone = true;
+        }
+        if let Some(slot) = self
+            .get_component_mut(column_id)
+            .and_then(|layout| layout.columns.get_mut(column_index))
+        {
+            slot.children.insert(index, component_id.to_string());
         }
     }
 
-    pub fn set_currently_dragging(&self, component_id: Option<String>) {
-        unsafe {
-            CURRENTLY_DRAGGING.set(component_id);
+    pub fn set_component_hidden(&mut self, component_id: &str, hidden: bool) {
+        if let Some(component) = self.get_component_mut(component_id) {
+            component.hidden = hidden;
         }
     }
 
-    pub fn take_currently_dragging(&self) -> Option<String> {
-        unsafe {
-            let current = CURRENTLY_DRAGGING();
-            CURRENTLY_DRAGGING.set(None);
-            current
-        }
-    }
-
-    pub fn add_to_column(&self, component_id: &str, column_id: &str, column_index: usize) {
-        unsafe {
-            COMPONENTS.with_mut(|components| {
-                // Find the component and column
-                let mut component_to_move = None;
-                let mut column_component = None;
-                
-                for component in components.iter_mut() {
-                    if component.id == component_id {
-                        component_to_move = Some(component.clone());
-                        component.parent_id = Some(column_id.to_string());
-                        component.in_drop_zone = true;
-                        // Calculate position within column
-                        let column_width = match self.get_column_count(column_id) {
-                            1 => 100.0,
-                            2 => 50.0,
-                            3 => 33.33,
-                            4 => 25.0,
-                            5 => 20.0,
-                            _ => 50.0,
-                        };
-                        component.position = (column_index as f64 * column_width, 10.0);
-                    }
-                    if component.id == column_id {
-                        column_component = Some(component);
-                    }
-                }
-                
-                // Add to column's children
-                if let Some(column) = column_component {
-                    if !column.children.contains(&component_id.to_string()) {
-                        column.children.push(component_id.to_string());
-                    }
-                }
-            });
-        }
-    }
-
-    pub fn get_column_count(&self, column_id: &str) -> usize {
-        unsafe {
-            for component in COMPONENTS().iter() {
-                if component.id == column_id {
-                    return match component.component_type {
-                        ComponentType::OneColumn => 1,
-                        ComponentType::TwoColumns => 2,
-                        ComponentType::ThreeColumns => 3,
-                        ComponentType::FourColumns => 4,
-                        ComponentType::FiveColumns => 5,
-                        _ => 1,
-                    };
-                }
+    pub fn set_component_locked(&mut self, component_id: &str, locked: bool) {
+        if let Some(component) = self.get_component_mut(component_id) {
+            component.locked = locked;
+        }
+    }
+
+    /// Gives a layout `count` columns. Children of columns that are removed move to the end of
+    /// the last remaining column.
+    pub fn set_grid_column_count(&mut self, component_id: &str, count: usize) {
+        let count = count.clamp(1, MAX_COLUMNS);
+        let Some(layout) = self.get_unlocked_component_mut(component_id).filter(|c| c.component_type.is_layout()) else {
+            return;
+        };
+        if count < layout.columns.len() {
+            let removed: Vec<String> = layout.columns.drain(count..).flat_map(|slot| slot.children).collect();
+            layout.columns[count - 1].children.extend(removed);
+        } else {
+            layout.columns.resize(count, ColumnSlot::default());
+        }
+    }
+
+    pub fn set_grid_settings(&mut self, component_id: &str, settings: GridSettings) {
+        if let Some(layout) = self.get_unlocked_component_mut(component_id).filter(|c| c.component_type.is_layout()) {
+            layout.grid = Some(settings);
+        }
+    }
+
+    pub fn set_column_span(&mut self, component_id: &str, column_index: usize, span: usize) {
+        if let Some(slot) = self
+            .get_unlocked_component_mut(component_id)
+            .and_then(|layout| layout.columns.get_mut(column_index))
+        {
+            slot.span = span.clamp(1, MAX_COLUMNS);
+        }
+    }
+
+    /// Where `component_id` sits inside a layout: the layout's id, the column index and its
+    /// index among the column's children.
+    pub fn position_in_column(&self, component_id: &str) -> Option<(String, usize, usize)> {
+        let parent_id = self.get_component(component_id)?.parent_id.clone()?;
+        let parent = self.get_component(&parent_id)?;
+        parent.columns.iter().enumerate().find_map(|(column_index, slot)| {
+            let index = slot.children.iter().position(|child| child == component_id)?;
+            Some((parent_id.clone(), column_index, index))
+        })
+    }
+
+    /// Moves `component_id` to position `index` among the canvas's top-level components, which
+    /// are shown in document order. A component in a column, or not placed yet, is taken onto
+    /// the canvas in the same step.
+    pub fn move_to_root_index(&mut self, component_id: &str, index: usize) {
+        if !self.can_move(component_id) {
+            return;
+        }
+        self.detach_from_parent(component_id);
+        let is_root = |c: &DraggableComponent| c.in_drop_zone && c.parent_id.is_none();
+        let components = &mut self.page_mut().components;
+        let Some(current) = components.iter().position(|c| c.id == component_id) else {
+            return;
+        };
+        let mut component = components.remove(current);
+        component.in_drop_zone = true;
+        let roots: Vec<usize> = (0..components.len()).filter(|&i| is_root(&components[i])).collect();
+        let position = match roots.get(index) {
+            Some(&position) => position,
+            None => roots.last().map_or(components.len(), |&last| last + 1),
+        };
+        components.insert(position, component);
+    }
+
+    /// Removes `component_id` from its parent's column slots and clears its `parent_id`.
+    fn detach_from_parent(&mut self, component_id: &str) {
+        let parent_id = self.get_component(component_id).and_then(|c| c.parent_id.clone());
+        if let Some(parent) = parent_id.and_then(|parent_id| self.get_component_mut(&parent_id)) {
+            for slot in parent.columns.iter_mut() {
+                slot.children.retain(|child| child != component_id);
             }
-            1
+        }
+        if let Some(component) = self.get_component_mut(component_id) {
+            component.parent_id = None;
+        }
+    }
+
+    /// Children in column `column_index` of the layout `column_id`, in display order.
+    pub fn get_column_children(&self, column_id: &str, column_index: usize) -> Vec<DraggableComponent> {
+        match self.get_component(column_id) {
+            Some(layout) => column_children(layout, column_index, &self.page().components)
+                .into_iter()
+                .cloned()
+                .collect(),
+            None => Vec::new(),
         }
     }
+}
 
-    pub fn get_components_in_column(&self, column_id: &str) -> Vec<DraggableComponent> {
-        unsafe {
-            COMPONENTS().into_iter()
-                .filter(|c| c.parent_id.as_ref() == Some(&column_id.to_string()))
+/// Children in column slot `column_index` of `layout`, in display order.
+pub(super) fn column_children<'a>(layout: &DraggableComponent, column_index: usize, all_components: &'a [DraggableComponent]) -> Vec<&'a DraggableComponent> {
+    layout
+        .columns
+        .get(column_index)
+        .map(|slot| {
+            slot.children
+                .iter()
+                .filter_map(|child_id| all_components.iter().find(|c| &c.id == child_id))
                 .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Handle to the document store owned by the nearest [`DragDropProvider`].
+///
+/// The handle is `Copy`, so it can be moved into as many event handlers as needed; every
+/// clone talks to the same store. Reading through it subscribes the calling component.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DragDropContext {
+    document: Signal<Document>,
+    history: Signal<History>,
+    currently_dragging: Signal<Option<String>>,
+    /// Type of the new component being dragged out of the blocks panel. It is only added to
+    /// the document once it is dropped somewhere that takes it.
+    dragged_block: Signal<Option<ComponentType>>,
+    /// Id of the library asset being dragged out of the Assets panel.
+    dragged_asset: Signal<Option<String>>,
+    /// Contents of the assets only this project holds, by asset id. Kept out of the document
+    /// so autosaves and undo steps don't copy them; gone once the page is closed.
+    selected_component: Signal<Option<String>>,
+    save_status: Signal<SaveStatus>,
+    /// Name of the server project the document was last loaded from or saved to.
+    project_name: Signal<Option<String>>,
+    /// Screen size the canvas is previewed at.
+    preview_device: Signal<Device>,
+    /// Theme palette the canvas is previewed in.
+    preview_scheme: Signal<ColorScheme>,
+    /// Breakpoint the inspector's class edits apply to; `None` edits the unprefixed classes.
+    style_breakpoint: Signal<Option<Breakpoint>>,
+    /// Name the document is saved under locally.
+    storage_key: Signal<String>,
+}
+
+impl DragDropContext {
+    /// Creates a new store seeded with `document` that saves under `storage_key`. Must be called
+    /// inside a Dioxus scope.
+    pub fn new(document: Document, storage_key: &str) -> Self {
+        Self {
+            document: Signal::new(document),
+            history: Signal::new(History::new()),
+            currently_dragging: Signal::new(None),
+            dragged_block: Signal::new(None),
+            dragged_asset: Signal::new(None),
+            selected_component: Signal::new(None),
+            save_status: Signal::new(SaveStatus::Unchanged),
+            project_name: Signal::new(None),
+            preview_device: Signal::new(Device::Desktop),
+            preview_scheme: Signal::new(ColorScheme::Light),
+            style_breakpoint: Signal::new(None),
+            storage_key: Signal::new(storage_key.to_string()),
         }
     }
 
-    pub fn export_to_html(&self) -> String {
-        let components = self.get_components_in_drop_zone();
-        let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n    <title>Generated Layout</title>\n    <script src=\"https://cdn.tailwindcss.com\"></script>\n</head>\n<body>\n");
-        
-        for component in components.iter().filter(|c| c.parent_id.is_none()) {
-            html.push_str(&self.component_to_html(component, &components));
+    /// A snapshot of the whole document.
+    pub fn document(&self) -> Document {
+        self.document.read().clone()
+    }
+
+    /// Writes `document` to local storage and records the outcome for [`Self::save_status`].
+    pub fn save(&self, document: &Document) {
+        let status = match persistence::save_document(&self.storage_key.peek(), document) {
+            Ok(()) => SaveStatus::Saved(chrono::Local::now()),
+            Err(err) => {
+                log::error!("Failed to save document: {err}");
+                SaveStatus::Failed(err)
+            }
+        };
+        self.set_save_status(status);
+    }
+
+    /// Records the outcome of a save made elsewhere, such as to the project store.
+    pub fn set_save_status(&self, status: SaveStatus) {
+        let mut save_status = self.save_status;
+        save_status.set(status);
+    }
+
+    pub fn save_now(&self) {
+        self.save(&self.document.peek());
+    }
+
+    pub fn save_status(&self) -> SaveStatus {
+        self.save_status.read().clone()
+    }
+
+    pub fn theme(&self) -> ThemeSettings {
+        self.document.read().theme.clone()
+    }
+
+    /// Applies `edit` to the theme. Consecutive edits to the same `field` are one undo step.
+    pub fn update_theme(&self, field: &'static str, edit: impl FnOnce(&mut ThemeSettings)) {
+        self.apply(Command::UpdateTheme { field }, |document| edit(&mut document.theme));
+    }
+
+    pub fn set_theme(&self, theme: ThemeSettings) {
+        self.apply(Command::UpdateTheme { field: "all" }, |document| document.theme = theme);
+    }
+
+    pub fn assets(&self) -> Vec<Asset> {
+        self.document.read().assets.clone()
+    }
+
+    pub fn add_asset(&self, asset: Asset) {
+        let command = Command::AddAsset {
+            asset_id: asset.id.clone(),
+        };
+        self.apply(command, |document| document.add_asset(asset));
+    }
+
+    pub fn rename_asset(&self, asset_id: &str, name: &str) {
+        let command = Command::UpdateAsset { asset_id: asset_id.to_string() };
+        self.apply(command, |document| document.rename_asset(asset_id, name));
+    }
+
+    pub fn remove_asset(&self, asset_id: &str) {
+        let command = Command::RemoveAsset { asset_id: asset_id.to_string() };
+        self.apply(command, |document| document.remove_asset(asset_id));
+    }
+
+    pub fn pages_using_asset(&self, asset_id: &str) -> Vec<String> {
+        self.document.read().pages_using_asset(asset_id)
+    }
+
+    /// Swaps in a whole new document. This is one undoable step.
+    pub fn replace_document(&self, document: Document) {
+        self.apply(Command::ReplaceDocument, |current| *current = document);
+        self.select_component(None);
+    }
+
+    pub fn pages(&self) -> Vec<Page> {
+        self.document.read().pages.clone()
+    }
+
+    pub fn active_page_id(&self) -> String {
+        self.document.read().page().id.clone()
+    }
+
+    /// Opens `page_id` on the canvas. Switching pages is not an undo step of its own.
+    pub fn select_page(&self, page_id: &str) {
+        let mut document = self.document;
+        document.with_mut(|document| document.select_page(page_id));
+        self.select_component(None);
+    }
+
+    pub fn add_page(&self, name: &str) -> String {
+        let page_id = format!("page_{}", self.document.peek().next_id);
+        self.apply(Command::AddPage { page_id }, |document| document.add_page(name))
+    }
+
+    pub fn rename_page(&self, page_id: &str, name: &str) {
+        let command = Command::RenamePage {
+            page_id: page_id.to_string(),
+        };
+        self.apply(command, |document| document.rename_page(page_id, name));
+    }
+
+    pub fn set_page_slug(&self, page_id: &str, slug: &str) {
+        let command = Command::SetPageSlug {
+            page_id: page_id.to_string(),
+        };
+        self.apply(command, |document| document.set_page_slug(page_id, slug));
+    }
+
+    pub fn duplicate_page(&self, page_id: &str) {
+        let command = Command::DuplicatePage {
+            page_id: page_id.to_string(),
+        };
+        self.apply(command, |document| document.duplicate_page(page_id));
+        self.select_component(None);
+    }
+
+    pub fn move_page(&self, page_id: &str, index: usize) {
+        let command = Command::MovePage {
+            page_id: page_id.to_string(),
+        };
+        self.apply(command, |document| document.move_page(page_id, index));
+    }
+
+    pub fn remove_page(&self, page_id: &str) {
+        let command = Command::RemovePage {
+            page_id: page_id.to_string(),
+        };
+        self.apply(command, |document| document.remove_page(page_id));
+        self.select_component(None);
+    }
+
+    pub fn project_name(&self) -> Option<String> {
+        self.project_name.read().clone()
+    }
+
+    pub fn set_project_name(&self, name: Option<String>) {
+        let mut project_name = self.project_name;
+        project_name.set(name);
+    }
+
+    /// Applies `mutation` to the document and records it in the undo history as `command`.
+    fn apply<R>(&self, command: Command, mutation: impl FnOnce(&mut Document) -> R) -> R {
+        let mut document = self.document;
+        let mut history = self.history;
+
+        let before = Patch::capture(&document.peek(), &command);
+        let result = document.with_mut(mutation);
+        history.with_mut(|history| history.record(command, before, &document.peek()));
+
+        result
+    }
+
+    pub fn undo(&self) {
+        let mut history = self.history;
+        let mut document = self.document;
+        if history.peek().can_undo() {
+            document.with_mut(|document| history.with_mut(|history| history.undo(document)));
         }
-        
-        html.push_str("</body>\n</html>");
-        html
     }
 
-    pub fn export_to_rsx(&self) -> String {
-        let components = self.get_components_in_drop_zone();
-        let mut rsx = String::from("rsx! {\n    div { class: \"min-h-screen bg-gray-50\",\n");
-        
-        for component in components.iter().filter(|c| c.parent_id.is_none()) {
-            rsx.push_str(&self.component_to_rsx(component, &components, 2));
+    pub fn redo(&self) {
+        let mut history = self.history;
+        let mut document = self.document;
+        if history.peek().can_redo() {
+            document.with_mut(|document| history.with_mut(|history| history.redo(document)));
         }
-        
-        rsx.push_str("    }\n}");
-        rsx
     }
 
-    fn component_to_html(&self, component: &DraggableComponent, all_components: &[DraggableComponent]) -> String {
-        match &component.component_type {
-            ComponentType::OneColumn | ComponentType::TwoColumns | ComponentType::ThreeColumns | 
-            ComponentType::FourColumns | ComponentType::FiveColumns => {
-                let column_count = match component.component_type {
-                    ComponentType::OneColumn => 1,
-                    ComponentType::TwoColumns => 2,
-                    ComponentType::ThreeColumns => 3,
-                    ComponentType::FourColumns => 4,
-                    ComponentType::FiveColumns => 5,
-                    _ => 1,
-                };
-                
-                let grid_class = match column_count {
-                    1 => "grid-cols-1",
-                    2 => "grid-cols-2",
-                    3 => "grid-cols-3",
-                    4 => "grid-cols-4",
-                    5 => "grid-cols-5",
-                    _ => "grid-cols-1",
-                };
-                
-                let mut html = format!("<div class=\"grid {} gap-4 p-4\">\n", grid_class);
-                
-                let children: Vec<_> = all_components.iter()
-                    .filter(|c| c.parent_id.as_ref() == Some(&component.id))
-                    .collect();
-                
-                for i in 0..column_count {
-                    html.push_str("    <div class=\"min-h-32 border-2 border-dashed border-gray-300 p-2\">\n");
-                    
-                    // Add children in this column
-                    for child in children.iter() {
-                        // Simple positioning - could be enhanced
-                        if (child.position.0 / (100.0 / column_count as f64)) as usize == i {
-                            html.push_str(&self.component_to_html(child, all_components));
-                        }
-                    }
-                    
-                    html.push_str("    </div>\n");
-                }
-                
-                html.push_str("</div>\n");
-                html
-            },
-            ComponentType::Header => format!("<h1 class=\"text-xl font-bold text-gray-800\">{}</h1>\n", component.name),
-            ComponentType::Hero => format!("<div class=\"text-center p-4 bg-gradient-to-r from-blue-500 to-purple-600 text-white rounded\">\n    <h2 class=\"text-lg font-bold\">{}</h2>\n    <p class=\"text-sm\">This is a hero section</p>\n</div>\n", component.name),
-            ComponentType::Text => format!("<p class=\"text-gray-700\">{}</p>\n", component.name),
-            ComponentType::Button => format!("<button class=\"px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600\">{}</button>\n", component.name),
-            ComponentType::Card => format!("<div class=\"p-3 bg-white border border-gray-200 rounded-lg shadow-sm\">\n    <h3 class=\"font-semibold text-gray-800\">{}</h3>\n    <p class=\"text-sm text-gray-600\">Card content</p>\n</div>\n", component.name),
-            ComponentType::Footer => format!("<div class=\"p-2 bg-gray-800 text-white text-center rounded\">\n    <p class=\"text-sm\">{}</p>\n</div>\n", component.name),
-            _ => format!("<div class=\"p-2 border border-gray-300 rounded\">{}</div>\n", component.name),
-        }
-    }
-
-    fn component_to_rsx(&self, component: &DraggableComponent, all_components: &[DraggableComponent], indent: usize) -> String {
-        let spaces = " ".repeat(indent);
-        
-        match &component.component_type {
-            ComponentType::OneColumn | ComponentType::TwoColumns | ComponentType::ThreeColumns | 
-            ComponentType::FourColumns | ComponentType::FiveColumns => {
-                let column_count = match component.component_type {
-                    ComponentType::OneColumn => 1,
-                    ComponentType::TwoColumns => 2,
-                    ComponentType::ThreeColumns => 3,
-                    ComponentType::FourColumns => 4,
-                    ComponentType::FiveColumns => 5,
-                    _ => 1,
-                };
-                
-                let grid_class = match column_count {
-                    1 => "grid-cols-1",
-                    2 => "grid-cols-2",
-                    3 => "grid-cols-3",
-                    4 => "grid-cols-4",
-                    5 => "grid-cols-5",
-                    _ => "grid-cols-1",
-                };
-                
-                let mut rsx = format!("{}div {{ class: \"grid {} gap-4 p-4\",\n", spaces, grid_class);
-                
-                let children: Vec<_> = all_components.iter()
-                    .filter(|c| c.parent_id.as_ref() == Some(&component.id))
-                    .collect();
-                
-                for i in 0..column_count {
-                    rsx.push_str(&format!("{}    div {{ class: \"min-h-32 border-2 border-dashed border-gray-300 p-2\",\n", spaces));
-                    
-                    // Add children in this column
-                    for child in children.iter() {
-                        if (child.position.0 / (100.0 / column_count as f64)) as usize == i {
-                            rsx.push_str(&self.component_to_rsx(child, all_components, indent + 8));
-                        }
-                    }
-                    
-                    rsx.push_str(&format!("{}    }}\n", spaces));
-                }
-                
-                rsx.push_str(&format!("{}}}\n", spaces));
-                rsx
-            },
-            ComponentType::Header => format!("{}h1 {{ class: \"text-xl font-bold text-gray-800\", \"{}\" }}\n", spaces, component.name),
-            ComponentType::Hero => format!("{}div {{ class: \"text-center p-4 bg-gradient-to-r from-blue-500 to-purple-600 text-white rounded\",\n{}    h2 {{ class: \"text-lg font-bold\", \"{}\" }}\n{}    p {{ class: \"text-sm\", \"This is a hero section\" }}\n{}}}\n", spaces, spaces, component.name, spaces, spaces),
-            ComponentType::Text => format!("{}p {{ class: \"text-gray-700\", \"{}\" }}\n", spaces, component.name),
-            ComponentType::Button => format!("{}button {{ class: \"px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600\", \"{}\" }}\n", spaces, component.name),
-            ComponentType::Card => format!("{}div {{ class: \"p-3 bg-white border border-gray-200 rounded-lg shadow-sm\",\n{}    h3 {{ class: \"font-semibold text-gray-800\", \"{}\" }}\n{}    p {{ class: \"text-sm text-gray-600\", \"Card content\" }}\n{}}}\n", spaces, spaces, component.name, spaces, spaces),
-            ComponentType::Footer => format!("{}div {{ class: \"p-2 bg-gray-800 text-white text-center rounded\",\n{}    p {{ class: \"text-sm\", \"{}\" }}\n{}}}\n", spaces, spaces, component.name, spaces),
-            _ => format!("{}div {{ class: \"p-2 border border-gray-300 rounded\", \"{}\" }}\n", spaces, component.name),
+    pub fn can_undo(&self) -> bool {
+        self.history.read().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.read().can_redo()
+    }
+
+    pub fn add_component(&self, component_type: ComponentType) -> String {
+        let component_id = format!("component_{}", self.document.peek().next_id);
+        self.apply(Command::AddComponent { component_id }, |document| {
+            document.add_component(component_type)
+        })
+    }
+
+    pub fn move_to_drop_zone(&self, component_id: &str, position: (f64, f64)) {
+        let command = Command::MoveToDropZone {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.move_to_drop_zone(component_id, position));
+    }
+
+    pub fn remove_component(&self, component_id: &str) {
+        let command = Command::RemoveComponent {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.remove_component(component_id));
+    }
+
+    pub fn update_component_name(&self, component_id: &str, new_name: &str) {
+        let command = Command::UpdateComponentName {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.update_component_name(component_id, new_name));
+    }
+
+    pub fn update_component_content(&self, component_id: &str, content: &str) {
+        let command = Command::UpdateComponentContent {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.update_component_content(component_id, content));
+    }
+
+    pub fn add_component_styles(&self, component_id: &str, classes: &str) {
+        let command = Command::UpdateComponentStyles {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.add_component_styles(component_id, classes));
+    }
+
+    /// Adds `classes` at the picked [`Self::style_breakpoint`]: unprefixed unless a narrower
+    /// scope was picked, and re-prefixed if they already carry a breakpoint.
+    pub fn add_scoped_styles(&self, component_id: &str, classes: &str) {
+        let scope = self.style_breakpoint();
+        let classes: Vec<String> =
+            classes.split_whitespace().map(|class| Breakpoint::scope(scope, Breakpoint::split(class).1)).collect();
+        self.add_component_styles(component_id, &classes.join(" "));
+    }
+
+    pub fn remove_component_style(&self, component_id: &str, class: &str) {
+        let command = Command::UpdateComponentStyles {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.remove_component_style(component_id, class));
+    }
+
+    pub fn set_component_attribute(&self, component_id: &str, key: &str, value: &str) {
+        let command = Command::SetComponentAttribute {
+            component_id: component_id.to_string(),
+            key: key.to_string(),
+        };
+        self.apply(command, |document| {
+            document.set_component_attribute(component_id, key, value)
+        });
+    }
+
+    pub fn remove_component_attribute(&self, component_id: &str, key: &str) {
+        let command = Command::RemoveComponentAttribute {
+            component_id: component_id.to_string(),
+            key: key.to_string(),
+        };
+        self.apply(command, |document| document.remove_component_attribute(component_id, key));
+    }
+
+    pub fn get_component(&self, component_id: &str) -> Option<DraggableComponent> {
+        self.document.read().get_component(component_id).cloned()
+    }
+
+    pub fn select_component(&self, component_id: Option<String>) {
+        let mut selected_component = self.selected_component;
+        selected_component.set(component_id);
+    }
+
+    pub fn selected_component_id(&self) -> Option<String> {
+        self.selected_component.read().clone()
+    }
+
+    /// The selected component, if it still exists in the document.
+    pub fn selected_component(&self) -> Option<DraggableComponent> {
+        let component_id = self.selected_component_id()?;
+        self.get_component(&component_id)
+    }
+
+    pub fn get_components_in_palette(&self) -> Vec<DraggableComponent> {
+        self.document.read().get_components_in_palette()
+    }
+
+    pub fn get_components_in_drop_zone(&self) -> Vec<DraggableComponent> {
+        self.document.read().get_components_in_drop_zone()
+    }
+
+    pub fn set_currently_dragging(&self, component_id: Option<String>) {
+        let mut currently_dragging = self.currently_dragging;
+        currently_dragging.set(component_id);
+    }
+
+    pub fn set_dragged_block(&self, component_type: Option<ComponentType>) {
+        let mut dragged_block = self.dragged_block;
+        dragged_block.set(component_type);
+    }
+
+    /// The component being dragged, wherever it currently is. A block from the blocks panel is
+    /// described by an unplaced component with an empty id.
+    pub fn dragged_component(&self) -> Option<DraggableComponent> {
+        if let Some(component_type) = self.dragged_block.read().clone() {
+            return Some(DraggableComponent::new(String::new(), component_type));
+        }
+        let component_id = self.currently_dragging.read().clone()?;
+        self.get_component(&component_id)
+    }
+
+    /// Ends the drag and returns the dragged component's id. A block from the blocks panel is
+    /// created here, so call this only once the drop is taken; placing it right after is part
+    /// of the same undo step.
+    pub fn take_currently_dragging(&self) -> Option<String> {
+        let mut dragged_block = self.dragged_block;
+        if let Some(component_type) = dragged_block.take() {
+            return Some(self.add_component(component_type));
         }
+        let mut currently_dragging = self.currently_dragging;
+        currently_dragging.take()
+    }
+
+    pub fn set_dragged_asset(&self, asset_id: Option<String>) {
+        let mut dragged_asset = self.dragged_asset;
+        dragged_asset.set(asset_id);
+    }
+
+    /// The asset being dragged, if it is an image.
+    pub fn dragged_image_asset(&self) -> Option<Asset> {
+        let asset_id = self.dragged_asset.read().clone()?;
+        self.document.read().get_asset(&asset_id).filter(|asset| asset.is_image()).cloned()
+    }
+
+    pub fn is_dragging_asset(&self) -> bool {
+        self.dragged_asset.read().is_some()
+    }
+
+    pub fn take_dragged_asset(&self) -> Option<String> {
+        let mut dragged_asset = self.dragged_asset;
+        dragged_asset.take()
+    }
+
+    pub fn get_asset(&self, asset_id: &str) -> Option<Asset> {
+        self.document.read().get_asset(asset_id).cloned()
+    }
+
+    pub fn set_component_asset(&self, component_id: &str, asset_id: Option<&str>) {
+        let command = Command::SetComponentAsset { component_id: component_id.to_string() };
+        self.apply(command, |document| document.set_component_asset(component_id, asset_id));
+    }
+
+    /// Creates an Image component for the asset in a column, as one undo step.
+    pub fn insert_image_into_column(&self, asset_id: &str, layout_id: &str, column_index: usize, index: usize) {
+        let component_id = format!("component_{}", self.document.peek().next_id);
+        let command = Command::AddComponent { component_id };
+        self.apply(command, |document| document.insert_image_into_column(asset_id, layout_id, column_index, index));
+    }
+
+    pub fn insert_into_column(&self, component_id: &str, column_id: &str, column_index: usize, index: usize) {
+        let command = Command::InsertIntoColumn {
+            component_id: component_id.to_string(),
+            column_id: column_id.to_string(),
+        };
+        self.apply(command, |document| {
+            document.insert_into_column(component_id, column_id, column_index, index)
+        });
+    }
+
+    pub fn move_to_root_index(&self, component_id: &str, index: usize) {
+        let command = Command::MoveToRootIndex {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.move_to_root_index(component_id, index));
+    }
+
+    pub fn set_component_hidden(&self, component_id: &str, hidden: bool) {
+        let command = Command::SetComponentHidden {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.set_component_hidden(component_id, hidden));
+    }
+
+    pub fn set_component_locked(&self, component_id: &str, locked: bool) {
+        let command = Command::SetComponentLocked {
+            component_id: component_id.to_string(),
+        };
+        self.apply(command, |document| document.set_component_locked(component_id, locked));
+    }
+
+    pub fn position_in_column(&self, component_id: &str) -> Option<(String, usize, usize)> {
+        self.document.read().position_in_column(component_id)
+    }
+
+    pub fn set_grid_column_count(&self, component_id: &str, count: usize) {
+        let command = Command::UpdateGrid { component_id: component_id.to_string() };
+        self.apply(command, |document| document.set_grid_column_count(component_id, count));
+    }
+
+    pub fn set_grid_settings(&self, component_id: &str, settings: GridSettings) {
+        let command = Command::UpdateGrid { component_id: component_id.to_string() };
+        self.apply(command, |document| document.set_grid_settings(component_id, settings));
+    }
+
+    pub fn set_column_span(&self, component_id: &str, column_index: usize, span: usize) {
+        let command = Command::UpdateGrid { component_id: component_id.to_string() };
+        self.apply(command, |document| document.set_column_span(component_id, column_index, span));
+    }
+
+    pub fn is_within(&self, component_id: &str, ancestor_id: &str) -> bool {
+        self.document.read().is_within(component_id, ancestor_id)
+    }
+
+    pub fn preview_device(&self) -> Device {
+        *self.preview_device.read()
+    }
+
+    /// Previews the canvas at `device`. Class edits keep their scope until another is picked.
+    pub fn set_preview_device(&self, device: Device) {
+        let mut preview_device = self.preview_device;
+        preview_device.set(device);
+    }
+
+    pub fn preview_scheme(&self) -> ColorScheme {
+        *self.preview_scheme.read()
+    }
+
+    /// Previews the canvas in `scheme`'s colors; the Theme panel edits the same palette.
+    pub fn set_preview_scheme(&self, scheme: ColorScheme) {
+        let mut preview_scheme = self.preview_scheme;
+        preview_scheme.set(scheme);
+    }
+
+    pub fn style_breakpoint(&self) -> Option<Breakpoint> {
+        *self.style_breakpoint.read()
+    }
+
+    pub fn set_style_breakpoint(&self, breakpoint: Option<Breakpoint>) {
+        let mut style_breakpoint = self.style_breakpoint;
+        style_breakpoint.set(breakpoint);
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.currently_dragging.read().is_some() || self.dragged_block.read().is_some()
+    }
+
+    pub fn get_column_children(&self, column_id: &str, column_index: usize) -> Vec<DraggableComponent> {
+        self.document.read().get_column_children(column_id, column_index)
+    }
+
+    pub fn accepts_children(&self, layout_id: &str) -> bool {
+        self.document.read().accepts_children(layout_id)
+    }
+
+    /// One exported file per page.
+    pub fn export(&self, format: ExportFormat, options: ExportOptions) -> Vec<ExportedFile> {
+        format.export(&self.document.read(), options)
     }
 }
 
 pub fn use_drag_drop_context() -> DragDropContext {
-    DragDropContext::new()
+    use_context()
 }
 
+/// Owns a document store and shares it with every descendant through Dioxus context. Each
+/// provider gets its own store, so several builders can live on one page; give each one its
+/// own `storage_key` so they don't save over each other.
 #[component]
-pub fn DragDropProvider(children: Element) -> Element {
-    // Initialize the context
-    use_drag_drop_context();
-    
+pub fn DragDropProvider(
+    children: Element,
+    #[props(into, default = persistence::DEFAULT_STORAGE_KEY.to_string())] storage_key: String,
+) -> Element {
+    let drag_context = use_context_provider(|| {
+        let document = persistence::load_document(&storage_key).unwrap_or_else(Document::starter);
+        DragDropContext::new(document, &storage_key)
+    });
+    persistence::use_autosave(drag_context);
+
     rsx! {
         {children}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_never_end_up_inside_themselves() {
+        let mut document = Document::new();
+        let outer = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&outer, (0.0, 0.0));
+        let inner = document.add_component(ComponentType::Grid);
+        document.set_grid_column_count(&inner, 1);
+        document.add_to_column(&inner, &outer, 0);
+
+        document.add_to_column(&outer, &outer, 1);
+        document.add_to_column(&outer, &inner, 0);
+        assert_eq!(document.get_component(&outer).unwrap().parent_id, None);
+        assert!(document.get_column_children(&inner, 0).is_empty());
+
+        // Removing a layout takes its nested layouts with it
+        document.remove_component(&outer);
+        assert!(document.get_component(&inner).is_none());
+    }
+
+    /// A canvas layout holding a Text in its first column.
+    fn layout_with_text(document: &mut Document) -> (String, String) {
+        let layout = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&layout, (0.0, 0.0));
+        let text = document.add_component(ComponentType::Text);
+        document.add_to_column(&text, &layout, 0);
+        (layout, text)
+    }
+
+    #[test]
+    fn locked_components_stay_where_they_are() {
+        let mut document = Document::new();
+        let (layout, text) = layout_with_text(&mut document);
+        document.set_component_locked(&text, true);
+        let before = document.clone();
+
+        document.move_to_drop_zone(&text, (0.0, 0.0));
+        document.insert_into_column(&text, &layout, 1, 0);
+        document.remove_component(&text);
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn locked_descendants_keep_their_layout() {
+        let mut document = Document::new();
+        let (layout, text) = layout_with_text(&mut document);
+        document.set_component_locked(&text, true);
+        document.remove_component(&layout);
+        assert!(document.get_component(&layout).is_some());
+        assert!(document.get_component(&text).is_some());
+
+        document.set_component_locked(&text, false);
+        document.remove_component(&layout);
+        assert!(document.get_component(&text).is_none());
+    }
+
+    #[test]
+    fn locked_layouts_keep_their_columns() {
+        let mut document = Document::new();
+        let (layout, text) = layout_with_text(&mut document);
+        let button = document.add_component(ComponentType::Button);
+        document.set_component_locked(&layout, true);
+        let before = document.clone();
+
+        // Nothing joins, leaves or is removed from its columns
+        document.insert_into_column(&button, &layout, 1, 0);
+        document.move_to_drop_zone(&text, (0.0, 0.0));
+        document.remove_component(&text);
+        assert_eq!(document.insert_image_into_column("asset_1", &layout, 0, 0), None);
+        assert_eq!(document, before);
+
+        document.set_component_locked(&layout, false);
+        document.insert_into_column(&button, &layout, 1, 0);
+        assert_eq!(document.position_in_column(&button), Some((layout, 1, 0)));
+    }
+
+    #[test]
+    fn page_slugs_stay_unique_and_exportable() {
+        let mut document = Document::new();
+        let about = document.add_page("About");
+        assert_eq!(document.add_page("About"), "page_3");
+        assert_eq!(document.pages[2].slug, "about-2");
+
+        // `index` belongs to the home page; module names can't start with a digit
+        document.set_page_slug(&about, "Index");
+        assert_eq!(document.pages[1].slug, "index-2");
+        document.set_page_slug(&about, "2024 news");
+        assert_eq!(document.pages[1].slug, "page-2024-news");
+    }
+
+    #[test]
+    fn pages_are_duplicated_moved_and_removed() {
+        let mut document = Document::new();
+        let home = document.pages[0].id.clone();
+        let (layout, text) = layout_with_text(&mut document);
+        let copy = document.duplicate_page(&home).unwrap();
+
+        // The copy follows the original, open, with its own ids pointing at each other
+        let page_ids: Vec<_> = document.pages.iter().map(|page| page.id.clone()).collect();
+        assert_eq!(page_ids, [home.clone(), copy.clone()]);
+        assert_eq!(document.active_page, copy);
+        assert_eq!(document.pages[1].name, "Home (copy)");
+        assert_eq!(document.pages[1].slug, "home-copy");
+        assert!(document.get_component(&layout).is_none());
+        let new_layout = document.get_components_in_drop_zone().into_iter().find(|c| c.parent_id.is_none()).unwrap();
+        let children = document.get_column_children(&new_layout.id, 0);
+        assert_eq!(children.len(), 1);
+        assert_ne!(children[0].id, text);
+
+        document.move_page(&copy, 0);
+        assert_eq!(document.pages[0].id, copy);
+        document.move_page(&copy, 10);
+        assert_eq!(document.pages[1].id, copy);
+
+        // Removing the open page opens its neighbour; the last page stays
+        document.remove_page(&copy);
+        assert_eq!(document.active_page, home);
+        document.remove_page(&home);
+        assert_eq!(document.pages.len(), 1);
+        assert!(document.get_component(&text).is_some());
+    }
+
+    #[test]
+    fn layouts_move_out_of_columns_to_a_canvas_position() {
+        let mut document = Document::new();
+        let (first, _) = layout_with_text(&mut document);
+        let second = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&second, (0.0, 0.0));
+        let nested = document.add_component(ComponentType::Grid);
+        document.add_to_column(&nested, &first, 1);
+
+        document.move_to_root_index(&nested, 1);
+        let roots: Vec<_> = document.get_components_in_drop_zone().into_iter().filter(|c| c.parent_id.is_none()).map(|c| c.id).collect();
+        assert_eq!(roots, [first.clone(), nested.clone(), second]);
+        assert!(document.get_column_children(&first, 1).is_empty());
+
+        // Fresh from the blocks panel
+        let fresh = document.add_component(ComponentType::Grid);
+        document.move_to_root_index(&fresh, 0);
+        assert_eq!(document.get_components_in_drop_zone()[0].id, fresh);
+    }
+
+    #[test]
+    fn each_context_has_its_own_document_and_history() {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_runtime(|| {
+            ScopeId::ROOT.in_runtime(|| {
+                let first = DragDropContext::new(Document::new(), "first");
+                let second = DragDropContext::new(Document::new(), "second");
+                let layout = first.add_component(ComponentType::Grid);
+                first.move_to_drop_zone(&layout, (0.0, 0.0));
+                first.select_component(Some(layout.clone()));
+
+                assert_eq!(first.get_components_in_drop_zone().len(), 1);
+                assert!(second.get_components_in_drop_zone().is_empty());
+                assert!(first.can_undo());
+                assert!(!second.can_undo());
+                assert_eq!(first.selected_component_id(), Some(layout));
+                assert_eq!(second.selected_component_id(), None);
+            })
+        });
+    }
+
+    #[test]
+    fn class_edits_apply_at_every_size_until_a_scope_is_picked() {
+        let mut dom = VirtualDom::new(|| rsx! {});
+        dom.rebuild_in_place();
+        dom.in_runtime(|| {
+            ScopeId::ROOT.in_runtime(|| {
+                let context = DragDropContext::new(Document::new(), "styles");
+                let text = context.add_component(ComponentType::Text);
+                context.add_scoped_styles(&text, "p-4");
+                // Previewing another device doesn't change where edits go
+                context.set_preview_device(Device::Tablet);
+                context.add_scoped_styles(&text, "md:text-lg");
+                context.set_style_breakpoint(Some(Breakpoint::Md));
+                context.add_scoped_styles(&text, "p-8");
+                let styles = context.get_component(&text).unwrap().properties.styles;
+                assert_eq!(styles, ["p-4", "text-lg", "md:p-8"]);
+            })
+        });
+    }
+}
+
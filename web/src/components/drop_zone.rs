@@ -1,50 +1,67 @@
 use dioxus::prelude::*;
-use crate::components::{use_drag_drop_context, DraggableComponent, ComponentType};
+use crate::components::{resolve_classes, use_drag_drop_context, Breakpoint, ColorScheme, ComponentType, Device, DraggableComponent, ExportDialog, ExportFormat, SaveStatus};
 
 #[component]
 pub fn DropZone() -> Element {
     let drag_context = use_drag_drop_context();
+    let mut export_format = use_signal(|| None::<ExportFormat>);
+    // Components take their colors from the previewed palette through these variables
+    let theme_variables = drag_context.theme().scheme(drag_context.preview_scheme()).css_variables();
+    let canvas_style = match drag_context.preview_device().width() {
+        Some(width) => format!("max-width: {width}px; {theme_variables}"),
+        None => theme_variables,
+    };
     
-    // Initialize default content only once
-    let mut has_initialized = use_signal(|| false);
-    
-    use_effect({
-        let drag_context = drag_context.clone();
+    // Ctrl+Z / Ctrl+Shift+Z (Cmd on macOS) anywhere outside a text field, for as long as
+    // the canvas is mounted
+    let listener_id = use_hook(|| uuid::Uuid::new_v4().simple().to_string());
+    use_future({
+        let listener_id = listener_id.clone();
         move || {
-            if !has_initialized() {
-                let layout_components: Vec<_> = drag_context
-                    .get_components_in_drop_zone()
-                    .into_iter()
-                    .filter(|c| matches!(c.component_type, 
-                        ComponentType::OneColumn | ComponentType::TwoColumns | ComponentType::ThreeColumns |
-                        ComponentType::FourColumns | ComponentType::FiveColumns))
-                    .collect();
-                
-                // If no layout exists, create a default single column layout with pre-existing content
-                if layout_components.is_empty() {
-                    // Add default single column layout
-                    let default_layout_id = drag_context.add_component(ComponentType::OneColumn);
-                    drag_context.move_to_drop_zone(&default_layout_id, (20.0, 20.0));
-                    
-                    // Add welcome header
-                    let header_id = drag_context.add_component(ComponentType::Header);
-                    drag_context.update_component_name(&header_id, "Welcome");
-                    drag_context.add_to_column(&header_id, &default_layout_id, 0);
-                    
-                    // Add lorem ipsum text
-                    let text_id = drag_context.add_component(ComponentType::Text);
-                    drag_context.update_component_name(&text_id, "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris.");
-                    drag_context.add_to_column(&text_id, &default_layout_id, 0);
-                    
-                    has_initialized.set(true);
+            let listener_id = listener_id.clone();
+            async move {
+                let mut shortcuts = document::eval(
+                    r#"
+                    const id = await dioxus.recv();
+                    const onKeyDown = (e) => {
+                        if (!(e.ctrlKey || e.metaKey) || e.key.toLowerCase() !== "z") return;
+                        const target = e.target;
+                        if (target.isContentEditable || ["INPUT", "TEXTAREA", "SELECT"].includes(target.tagName)) return;
+                        e.preventDefault();
+                        dioxus.send(e.shiftKey ? "redo" : "undo");
+                    };
+                    window.builderShortcuts ??= new Map();
+                    window.builderShortcuts.set(id, onKeyDown);
+                    window.addEventListener("keydown", onKeyDown);
+                    "#,
+                );
+                if let Err(err) = shortcuts.send(&listener_id) {
+                    log::warn!("Could not listen for undo shortcuts: {err:?}");
+                    return;
+                }
+                while let Ok(action) = shortcuts.recv::<String>().await {
+                    match action.as_str() {
+                        "undo" => drag_context.undo(),
+                        "redo" => drag_context.redo(),
+                        _ => {}
+                    }
                 }
             }
         }
     });
-    
-    let drag_context_drop = drag_context.clone();
-    let drag_context_html = drag_context.clone();
-    let drag_context_rsx = drag_context.clone();
+    use_drop(move || {
+        let eval = document::eval(
+            r#"
+            const id = await dioxus.recv();
+            const onKeyDown = window.builderShortcuts?.get(id);
+            if (onKeyDown) window.removeEventListener("keydown", onKeyDown);
+            window.builderShortcuts?.delete(id);
+            "#,
+        );
+        if let Err(err) = eval.send(listener_id) {
+            log::warn!("Could not stop listening for undo shortcuts: {err:?}");
+        }
+    });
     
     rsx! {
         div {
@@ -53,47 +70,26 @@ pub fn DropZone() -> Element {
             ondragover: move |e| e.prevent_default(),
             ondrop: move |e| {
                 e.prevent_default();
-                if let Some(dragged_id) = drag_context_drop.take_currently_dragging() {
-                    let all_components = drag_context_drop.get_components_in_palette();
-                    let in_drop_zone_components = drag_context_drop
-                        .get_components_in_drop_zone();
-                    if let Some(dragged_component) = all_components
-                        .iter()
-                        .chain(in_drop_zone_components.iter())
-                        .find(|c| c.id == dragged_id)
-                    {
-                        match dragged_component.component_type {
-                            ComponentType::OneColumn
-                            | ComponentType::TwoColumns
-                            | ComponentType::ThreeColumns
-                            | ComponentType::FourColumns
-                            | ComponentType::FiveColumns => {
-                                let existing_layouts = drag_context_drop
-                                    .get_components_in_drop_zone()
-                                    .into_iter()
-                                    .filter(|c| c.parent_id.is_none())
-                                    .filter(|c| {
-                                        matches!(
-                                            c.component_type,
-                                            ComponentType::OneColumn
-                                            | ComponentType::TwoColumns
-                                            | ComponentType::ThreeColumns
-                                            | ComponentType::FourColumns
-                                            | ComponentType::FiveColumns
-                                        )
-                                    })
-                                    .collect::<Vec<_>>();
-                                let y_position = existing_layouts.len() as f64 * 200.0 + 20.0;
-                                drag_context_drop
-                                    .move_to_drop_zone(&dragged_id, (20.0, y_position));
-                            }
-                            _ => {
-                                web_sys::console::log_1(
-                                    &"Content components must be dropped into column areas!"
-                                        .into(),
-                                );
-                            }
+                if drag_context.take_dragged_asset().is_some() {
+                    web_sys::console::log_1(&"Assets must be dropped into column areas!".into());
+                }
+                if let Some(dragged_component) = drag_context.dragged_component() {
+                    if dragged_component.component_type.is_layout() {
+                        let existing_layouts = drag_context
+                            .get_components_in_drop_zone()
+                            .into_iter()
+                            .filter(|c| c.parent_id.is_none())
+                            .filter(|c| c.component_type.is_layout())
+                            .collect::<Vec<_>>();
+                        let y_position = existing_layouts.len() as f64 * 200.0 + 20.0;
+                        if let Some(dragged_id) = drag_context.take_currently_dragging() {
+                            drag_context.move_to_drop_zone(&dragged_id, (20.0, y_position));
                         }
+                    } else {
+                        web_sys::console::log_1(
+                            &"Content components must be dropped into column areas!"
+                                .into(),
+                        );
                     }
                 }
             },
@@ -101,43 +97,63 @@ pub fn DropZone() -> Element {
             div { class: "text-center text-gray-500 mb-4",
                 h2 { class: "text-2xl font-semibold mb-2", "Website Builder Canvas" }
                 p { "Drag layout components here first, then add content to the columns" }
+                DeviceToolbar {}
+                ColorSchemeToggle {}
+            }
+            // Autosave failures stay in view until a save works again
+            if let SaveStatus::Failed(err) = drag_context.save_status() {
+                div {
+                    class: "mb-4 p-3 rounded-lg border border-red-200 bg-red-50 text-sm text-red-700",
+                    role: "alert",
+                    "Your changes are not being saved: {err}"
+                }
+            }
+            // History buttons
+            div { class: "absolute top-4 left-4 flex gap-2",
+                button {
+                    class: "px-3 py-1 bg-white border border-gray-300 text-gray-700 rounded text-sm hover:bg-gray-100 disabled:opacity-40 disabled:cursor-not-allowed",
+                    title: "Undo (Ctrl+Z)",
+                    disabled: !drag_context.can_undo(),
+                    onclick: move |_| drag_context.undo(),
+                    "Undo"
+                }
+                button {
+                    class: "px-3 py-1 bg-white border border-gray-300 text-gray-700 rounded text-sm hover:bg-gray-100 disabled:opacity-40 disabled:cursor-not-allowed",
+                    title: "Redo (Ctrl+Shift+Z)",
+                    disabled: !drag_context.can_redo(),
+                    onclick: move |_| drag_context.redo(),
+                    "Redo"
+                }
             }
             // Export buttons
             div { class: "absolute top-4 right-4 flex gap-2",
                 button {
                     class: "px-3 py-1 bg-blue-500 text-white rounded text-sm hover:bg-blue-600",
-                    onclick: move |_| {
-                        let html = drag_context_html.export_to_html();
-                        web_sys::console::log_1(&format!("HTML Export:\n{}", html).into());
-                    },
+                    onclick: move |_| export_format.set(Some(ExportFormat::Html)),
                     "Export HTML"
                 }
                 button {
                     class: "px-3 py-1 bg-green-500 text-white rounded text-sm hover:bg-green-600",
-                    onclick: move |_| {
-                        let rsx = drag_context_rsx.export_to_rsx();
-                        web_sys::console::log_1(&format!("RSX Export:\n{}", rsx).into());
-                    },
+                    onclick: move |_| export_format.set(Some(ExportFormat::Rsx)),
                     "Export RSX"
                 }
             }
+            if let Some(format) = export_format() {
+                ExportDialog {
+                    initial_format: format,
+                    on_close: move |_| export_format.set(None),
+                }
+            }
             // Render layout components vertically stacked - GrapeJS style preview
-            div { class: "space-y-4 mt-8 bg-white rounded-lg shadow-sm border min-h-96",
+            div {
+                class: "space-y-4 mt-8 mx-auto w-full bg-[var(--theme-background)] text-[var(--theme-text)] rounded-lg shadow-sm border min-h-96 transition-all",
+                style: canvas_style,
                 {
                     drag_context
                         .get_components_in_drop_zone()
                         .into_iter()
-                        .filter(|c| c.parent_id.is_none())
-                        .filter(|c| {
-                            matches!(
-                                c.component_type,
-                                ComponentType::OneColumn
-                                | ComponentType::TwoColumns
-                                | ComponentType::ThreeColumns
-                                | ComponentType::FourColumns
-                                | ComponentType::FiveColumns
-                            )
-                        })
+                        .filter(|c| c.parent_id.is_none() && !c.hidden)
+                        .filter(|c| c.component_type.is_layout())
                         .map(|component| {
                             let component_id = component.id.clone();
                             rsx! {
@@ -150,113 +166,200 @@ pub fn DropZone() -> Element {
     }
 }
 
+/// Switches the canvas between desktop, tablet and mobile widths.
+#[component]
+fn DeviceToolbar() -> Element {
+    let drag_context = use_drag_drop_context();
+    let active = drag_context.preview_device();
+
+    rsx! {
+        div { class: "inline-flex mt-4 rounded-lg border border-gray-300 bg-white overflow-hidden",
+            for device in Device::ALL {
+                button {
+                    class: "flex items-center gap-2 px-3 py-1.5 text-sm",
+                    class: if device == active { "bg-gray-800 text-white" } else { "text-gray-600 hover:bg-gray-100" },
+                    title: match device.width() {
+                        Some(width) => format!("{} ({width}px)", device.label()),
+                        None => device.label().to_string(),
+                    },
+                    onclick: move |_| drag_context.set_preview_device(device),
+                    svg {
+                        class: "w-4 h-4",
+                        fill: "none",
+                        stroke: "currentColor",
+                        view_box: "0 0 24 24",
+                        path {
+                            stroke_linecap: "round",
+                            stroke_linejoin: "round",
+                            stroke_width: "2",
+                            d: device.icon(),
+                        }
+                    }
+                    "{device.label()}"
+                }
+            }
+        }
+    }
+}
+
+/// Previews the canvas in the theme's light or dark palette.
+#[component]
+fn ColorSchemeToggle() -> Element {
+    let drag_context = use_drag_drop_context();
+    let active = drag_context.preview_scheme();
+
+    rsx! {
+        div { class: "inline-flex mt-4 ml-2 rounded-lg border border-gray-300 bg-white overflow-hidden",
+            for scheme in ColorScheme::ALL {
+                button {
+                    class: "px-3 py-1.5 text-sm",
+                    class: if scheme == active { "bg-gray-800 text-white" } else { "text-gray-600 hover:bg-gray-100" },
+                    title: "Preview the theme's {scheme.label().to_lowercase()} colors",
+                    onclick: move |_| drag_context.set_preview_scheme(scheme),
+                    "{scheme.label()}"
+                }
+            }
+        }
+    }
+}
+
+/// `component`'s classes on top of `base`, as they apply at the previewed `breakpoint`.
+fn canvas_class(component: &DraggableComponent, base: &str, breakpoint: Option<Breakpoint>) -> String {
+    resolve_classes(&component.class_list(base), breakpoint)
+}
+
+/// DOM id of a component's element on the canvas.
+pub fn canvas_element_id(component_id: &str) -> String {
+    format!("canvas-{component_id}")
+}
+
 #[component]
 pub fn DroppedLayoutComponent(component: DraggableComponent) -> Element {
     let drag_context = use_drag_drop_context();
     let component_id = component.id.clone();
     let component_id_for_delete = component.id.clone();
-    let drag_context_delete = drag_context.clone();
+    let component_id_for_select = component.id.clone();
+    let is_selected = drag_context.selected_component_id().as_ref() == Some(&component.id);
+    let locked = component.locked;
     
     rsx! {
         div {
-            class: "bg-white border border-gray-200 rounded-lg p-4 shadow-sm hover:shadow-md transition-shadow w-full relative group",
-            draggable: "true",
-            ondragstart: move |_e| {
+            id: canvas_element_id(&component.id),
+            class: "bg-[var(--theme-background)] border border-gray-200 rounded-lg p-4 shadow-sm hover:shadow-md transition-shadow w-full relative group",
+            class: if is_selected { "ring-2 ring-blue-500" },
+            draggable: if locked { "false" } else { "true" },
+            ondragstart: move |e| {
+                if locked {
+                    e.prevent_default();
+                    return;
+                }
                 drag_context.set_currently_dragging(Some(component_id.clone()));
             },
+            ondragend: move |_| drag_context.set_currently_dragging(None),
+            onclick: move |_| {
+                drag_context.select_component(Some(component_id_for_select.clone()));
+            },
             // Delete button - only visible on hover
-            button {
-                class: "absolute -top-2 -right-2 w-6 h-6 bg-red-500 text-white rounded-full text-xs hover:bg-red-600 opacity-0 group-hover:opacity-100 transition-opacity z-10",
-                onclick: move |_| {
-                    drag_context_delete.remove_component(&component_id_for_delete);
-                },
-                "×"
+            if !locked {
+                button {
+                    class: "absolute -top-2 -right-2 w-6 h-6 bg-red-500 text-white rounded-full text-xs hover:bg-red-600 opacity-0 group-hover:opacity-100 transition-opacity z-10",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        drag_context.remove_component(&component_id_for_delete);
+                    },
+                    "×"
+                }
             }
             // Layout header
             div { class: "flex items-center justify-between mb-3 pb-2 border-b border-gray-100",
-                h3 { class: "text-sm font-medium text-gray-700", "{component.name}" }
-                span { class: "text-xs text-gray-400 bg-gray-100 px-2 py-1 rounded",
-                    {
-                        match component.component_type {
-                            ComponentType::OneColumn => "1 Column",
-                            ComponentType::TwoColumns => "2 Columns",
-                            ComponentType::ThreeColumns => "3 Columns",
-                            ComponentType::FourColumns => "4 Columns",
-                            ComponentType::FiveColumns => "5 Columns",
-                            _ => "Layout",
-                        }
+                h3 { class: "text-sm font-medium text-gray-700",
+                    "{component.name}"
+                    if locked {
+                        span { class: "ml-2 text-xs text-gray-400 bg-gray-100 px-1.5 py-0.5 rounded", "Locked" }
                     }
                 }
+                span { class: "text-xs text-gray-400 bg-gray-100 px-2 py-1 rounded",
+                    if component.columns.len() == 1 { "1 Column" } else { "{component.columns.len()} Columns" }
+                }
             }
             // Column layout content - more GrapeJS-like
-            {render_layout_content(&component)}
+            {render_layout_content(&component, drag_context.preview_device().breakpoint())}
         }
     }
 }
 
-fn render_layout_content(component: &DraggableComponent) -> Element {
-    match &component.component_type {
-        ComponentType::OneColumn | ComponentType::TwoColumns | ComponentType::ThreeColumns | 
-        ComponentType::FourColumns | ComponentType::FiveColumns => {
-            let column_count = match component.component_type {
-                ComponentType::OneColumn => 1,
-                ComponentType::TwoColumns => 2,
-                ComponentType::ThreeColumns => 3,
-                ComponentType::FourColumns => 4,
-                ComponentType::FiveColumns => 5,
-                _ => 1,
-            };
-            
-            let grid_class = match column_count {
-                1 => "grid-cols-1",
-                2 => "grid-cols-2",
-                3 => "grid-cols-3",
-                4 => "grid-cols-4",
-                5 => "grid-cols-5",
-                _ => "grid-cols-1",
-            };
-            
-            rsx! {
-                div { class: "w-full",
-                    // GrapeJS-style preview with actual rendered content
-                    div { class: "grid {grid_class} gap-3 min-h-32 w-full",
-                        {
-                            (0..column_count)
-                                .map(|i| {
-                                    let column_id = format!("{}-col-{}", component.id, i);
-                                    rsx! {
-                                        ColumnDropZone {
-                                            key: "{column_id}",
-                                            column_id,
-                                            parent_component_id: component.id.clone(),
-                                            column_index: i,
-                                        }
-                                    }
-                                })
-                        }
-                    }
+fn render_layout_content(component: &DraggableComponent, breakpoint: Option<Breakpoint>) -> Element {
+    if !component.component_type.is_layout() {
+        return rsx! {
+            div { class: "p-2 text-gray-500 text-sm", "This is not a layout component" }
+        };
+    }
+
+    rsx! {
+        div { class: "w-full",
+            // GrapeJS-style preview with actual rendered content
+            div { class: canvas_class(component, &format!("{} min-h-32 w-full", component.grid_class()), breakpoint),
+                {
+                    (0..component.columns.len())
+                        .map(|i| {
+                            let column_id = format!("{}-col-{}", component.id, i);
+                            rsx! {
+                                ColumnDropZone {
+                                    key: "{column_id}",
+                                    column_id,
+                                    parent_component_id: component.id.clone(),
+                                    column_index: i,
+                                    span_class: resolve_classes(component.column_span_class(i).unwrap_or_default(), breakpoint),
+                                }
+                            }
+                        })
                 }
             }
-        },
-        _ => rsx! {
-            div { class: "p-2 text-gray-500 text-sm", "This is not a layout component" }
         }
     }
 }
 
 #[component]
-pub fn ColumnDropZone(column_id: String, parent_component_id: String, column_index: usize) -> Element {
+pub fn ColumnDropZone(column_id: String, parent_component_id: String, column_index: usize, span_class: String) -> Element {
     let drag_context = use_drag_drop_context();
-    let drag_context_render = drag_context.clone();
-    let drag_context_check = drag_context.clone();
+    // Where a drop would land among this column's children, while something is dragged over it
+    let mut drop_index = use_signal(|| None::<usize>);
+    
+    let components_in_column = drag_context.get_column_children(&parent_component_id, column_index);
+    let child_count = components_in_column.len();
+    let visible_count = components_in_column.iter().filter(|c| !c.hidden).count();
+    let insertion_index = drop_index().filter(|_| drag_context.is_dragging() || drag_context.is_dragging_asset());
     
     rsx! {
         div {
             id: "{column_id}",
             class: "border border-dashed border-gray-200 min-h-32 p-3 bg-gray-50 rounded hover:border-blue-400 hover:bg-blue-50 transition-all w-full relative",
-            ondragover: move |e| e.prevent_default(),
+            class: "{span_class}",
+            ondragover: move |e| {
+                e.prevent_default();
+                // Outer columns of a nested layout would otherwise show an indicator too
+                e.stop_propagation();
+                // Only reached over empty space; children report their own insertion point
+                drop_index.set(Some(child_count));
+            },
+            ondragleave: move |_| drop_index.set(None),
             ondrop: move |e| {
                 e.prevent_default();
+                e.stop_propagation();
+                let index = drop_index.take().unwrap_or(child_count);
+                if !drag_context.accepts_children(&parent_component_id) {
+                    web_sys::console::log_1(&"This layout is locked!".into());
+                    return;
+                }
+                // An image from the Assets panel becomes a new Image component
+                if let Some(asset_id) = drag_context.take_dragged_asset() {
+                    if drag_context.get_asset(&asset_id).is_some_and(|asset| asset.is_image()) {
+                        drag_context.insert_image_into_column(&asset_id, &parent_component_id, column_index, index);
+                    } else {
+                        web_sys::console::log_1(&"Only images can be placed on the canvas!".into());
+                    }
+                    return;
+                }
                 if let Some(dragged_id) = drag_context.take_currently_dragging() {
                     let all_components = drag_context.get_components_in_palette();
                     let in_drop_zone_components = drag_context.get_components_in_drop_zone();
@@ -265,156 +368,188 @@ pub fn ColumnDropZone(column_id: String, parent_component_id: String, column_ind
                         .chain(in_drop_zone_components.iter())
                         .find(|c| c.id == dragged_id)
                     {
-                        match dragged_component.component_type {
-                            ComponentType::OneColumn
-                            | ComponentType::TwoColumns
-                            | ComponentType::ThreeColumns
-                            | ComponentType::FourColumns
-                            | ComponentType::FiveColumns => {
-                                web_sys::console::log_1(
-                                    &"Layout components cannot be dropped into columns!".into(),
+                        if drag_context.is_within(&parent_component_id, &dragged_component.id) {
+                            web_sys::console::log_1(
+                                &"A layout cannot be dropped into itself!".into(),
+                            );
+                        } else {
+                            drag_context
+                                .insert_into_column(
+                                    &dragged_id,
+                                    &parent_component_id,
+                                    column_index,
+                                    index,
                                 );
-                            }
-                            _ => {
-                                drag_context
-                                    .add_to_column(
-                                        &dragged_id,
-                                        &parent_component_id,
-                                        column_index,
-                                    );
-                            }
                         }
                     }
                 }
             },
             // Show column children - GrapeJS style rendering
-            {
-                drag_context_render
-                    .get_components_in_column(&parent_component_id)
-                    .into_iter()
-                    .filter(|c| {
-                        let column_count = drag_context_render
-                            .get_column_count(&parent_component_id);
-                        let column_width = 100.0 / column_count as f64;
-                        let component_column = (c.position.0 / column_width) as usize;
-                        component_column == column_index
-                    })
-                    .map(|component| {
-                        let component_id = component.id.clone();
-                        rsx! {
-                            ColumnComponent { key: "{component_id}", component: component.clone() }
-                        }
-                    })
+            // Hidden children keep their slot, so indices still match the column's order
+            for (index, component) in components_in_column.into_iter().enumerate() {
+                if insertion_index == Some(index) {
+                    InsertionIndicator {}
+                }
+                if !component.hidden {
+                    ColumnComponent {
+                        key: "{component.id}",
+                        component,
+                        on_drag_over: move |below: bool| drop_index.set(Some(index + below as usize)),
+                    }
+                }
+            }
+            if visible_count > 0 && insertion_index == Some(child_count) {
+                InsertionIndicator {}
             }
             // Empty state - GrapeJS style
-            {
-                let components_in_column = drag_context_check
-                    .get_components_in_column(&parent_component_id)
-                    .into_iter()
-                    .filter(|c| {
-                        let column_count = drag_context_check
-                            .get_column_count(&parent_component_id);
-                        let column_width = 100.0 / column_count as f64;
-                        let component_column = (c.position.0 / column_width) as usize;
-                        component_column == column_index
-                    })
-                    .collect::<Vec<_>>();
-                if components_in_column.is_empty() {
-                    rsx! {
-                        div { class: "text-xs text-gray-400 text-center py-8 flex flex-col items-center justify-center h-full min-h-24",
-                            div { class: "w-8 h-8 border-2 border-dashed border-gray-300 rounded mb-2 flex items-center justify-center",
-                                "+"
-                            }
-                            "Drop content here"
-                            span { class: "text-xs text-gray-300 mt-1", "Column {column_index + 1}" }
-                        }
+            if visible_count == 0 {
+                div { class: "text-xs text-gray-400 text-center py-8 flex flex-col items-center justify-center h-full min-h-24",
+                    div { class: "w-8 h-8 border-2 border-dashed border-gray-300 rounded mb-2 flex items-center justify-center",
+                        "+"
                     }
-                } else {
-                    rsx! {}
+                    "Drop content here"
+                    span { class: "text-xs text-gray-300 mt-1", "Column {column_index + 1}" }
                 }
             }
         }
     }
 }
 
+/// The line showing where a dragged component will be inserted.
+#[component]
+fn InsertionIndicator() -> Element {
+    rsx! {
+        div { class: "h-1 -my-0.5 mb-1.5 bg-blue-500 rounded-full pointer-events-none" }
+    }
+}
+
+/// A component inside a column. While anything is being dragged it reports, through
+/// `on_drag_over`, whether the pointer is over its lower half (`true`) or upper half.
 #[component]
-pub fn ColumnComponent(component: DraggableComponent) -> Element {
+pub fn ColumnComponent(component: DraggableComponent, on_drag_over: EventHandler<bool>) -> Element {
     let drag_context = use_drag_drop_context();
-    let drag_context_delete = drag_context.clone();
     let component_id = component.id.clone();
     let component_id_for_delete = component.id.clone();
+    let component_id_for_select = component.id.clone();
+    let is_selected = drag_context.selected_component_id().as_ref() == Some(&component.id);
+    let locked = component.locked;
+    let is_layout = component.component_type.is_layout();
+    // Dropping an image asset onto an Image replaces its picture
+    let takes_asset = component.component_type == ComponentType::Image
+        && !locked
+        && drag_context.dragged_image_asset().is_some();
+    let component_id_for_asset = component.id.clone();
     
     rsx! {
         div {
+            id: canvas_element_id(&component.id),
             class: "relative cursor-pointer transition-all duration-200 border border-transparent rounded mb-2 hover:border-blue-300 hover:bg-blue-50",
-            draggable: "true",
-            ondragstart: move |_e| {
+            class: if is_selected { "ring-2 ring-blue-500" },
+            draggable: if locked { "false" } else { "true" },
+            ondragstart: move |e| {
+                // Don't let the layout this sits in claim the drag
+                e.stop_propagation();
+                if locked {
+                    e.prevent_default();
+                    return;
+                }
                 drag_context.set_currently_dragging(Some(component_id.clone()));
             },
+            ondragend: move |_| drag_context.set_currently_dragging(None),
+            onclick: move |e| {
+                e.stop_propagation();
+                drag_context.select_component(Some(component_id_for_select.clone()));
+            },
             // Component toolbar - appears on hover
             div { class: "absolute -top-6 left-0 bg-blue-600 text-white text-xs px-2 py-1 rounded-t items-center gap-1 z-10 opacity-0 hover:opacity-100 group-hover:opacity-100 transition-opacity",
                 span { class: "font-medium", "{component.component_type:?}" }
-                button {
-                    class: "ml-1 hover:bg-blue-700 px-1 rounded",
-                    onclick: move |e| {
+                if locked {
+                    span { class: "ml-1 opacity-75", "Locked" }
+                } else {
+                    button {
+                        class: "ml-1 hover:bg-blue-700 px-1 rounded",
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            drag_context.remove_component(&component_id_for_delete);
+                        },
+                        "×"
+                    }
+                }
+            }
+            // Component content - render like real website content
+            {render_column_component_content(&component, drag_context.preview_device().breakpoint())}
+            // Drop targets for the upper and lower half, only present during a drag. A nested
+            // layout only gets thin edges, leaving its own columns free to take the drop.
+            if takes_asset {
+                div {
+                    class: "absolute inset-0 z-30 rounded border-2 border-dashed border-blue-400 bg-blue-50/60",
+                    ondragover: move |e| {
+                        e.prevent_default();
                         e.stop_propagation();
-                        drag_context_delete.remove_component(&component_id_for_delete);
                     },
-                    "×"
+                    ondrop: move |e| {
+                        e.prevent_default();
+                        e.stop_propagation();
+                        if let Some(asset_id) = drag_context.take_dragged_asset() {
+                            drag_context.set_component_asset(&component_id_for_asset, Some(&asset_id));
+                        }
+                    },
+                }
+            } else if drag_context.is_dragging() || drag_context.is_dragging_asset() {
+                for below in [false, true] {
+                    div {
+                        class: "absolute inset-x-0 z-20",
+                        class: if is_layout { "h-3" } else { "h-1/2" },
+                        class: if below { "bottom-0" } else { "top-0" },
+                        ondragover: move |e| {
+                            e.prevent_default();
+                            e.stop_propagation();
+                            on_drag_over.call(below);
+                        },
+                    }
                 }
             }
-            // Component content - render like real website content
-            {render_column_component_content(&component)}
         }
     }
 }
 
-fn render_column_component_content(component: &DraggableComponent) -> Element {
+fn render_column_component_content(component: &DraggableComponent, breakpoint: Option<Breakpoint>) -> Element {
+    let body = component.body().unwrap_or_default();
+
     match &component.component_type {
+        // A layout nested in a column, with its own columns to drop into
+        component_type if component_type.is_layout() => render_layout_content(component, breakpoint),
         ComponentType::Header => rsx! {
-            h1 { class: "text-2xl font-bold text-gray-800", "{component.name}" }
+            h1 { class: canvas_class(component, "text-2xl font-bold text-[var(--theme-text)]", breakpoint), "{component.name}" }
         },
         ComponentType::Hero => rsx! {
-            div { class: "text-center p-4 bg-gradient-to-r from-blue-500 to-purple-600 text-white rounded",
+            div { class: canvas_class(component, "text-center p-4 bg-gradient-to-r from-[var(--theme-primary)] to-[var(--theme-accent)] text-white rounded", breakpoint),
                 h2 { class: "text-xl font-bold mb-2", "{component.name}" }
-                p { class: "text-sm", "This is a hero section" }
+                p { class: "text-sm", "{body}" }
             }
         },
         ComponentType::Text => rsx! {
-            p { class: "text-gray-700 leading-relaxed", "{component.name}" }
+            p { class: canvas_class(component, "text-[var(--theme-text)] leading-relaxed", breakpoint), "{component.name}" }
         },
         ComponentType::Button => rsx! {
-            button { class: "px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600",
+            button { class: canvas_class(component, "px-4 py-2 bg-[var(--theme-button)] text-white rounded hover:opacity-90", breakpoint),
                 "{component.name}"
             }
         },
         ComponentType::Card => rsx! {
-            div { class: "p-4 bg-white border border-gray-200 rounded-lg shadow-sm",
-                h3 { class: "text-lg font-semibold text-gray-800 mb-2", "{component.name}" }
-                p { class: "text-sm text-gray-600", "Card content goes here" }
+            div { class: canvas_class(component, "p-4 bg-[var(--theme-background)] border border-gray-200 rounded-lg shadow-sm", breakpoint),
+                h3 { class: "text-lg font-semibold text-[var(--theme-primary)] mb-2", "{component.name}" }
+                p { class: "text-sm text-[var(--theme-text)]", "{body}" }
             }
         },
         ComponentType::Footer => rsx! {
-            div { class: "p-3 bg-gray-800 text-white text-center rounded", "{component.name}" }
+            div { class: canvas_class(component, "p-3 bg-[var(--theme-secondary)] text-white text-center rounded", breakpoint), "{component.name}" }
         },
         ComponentType::Image => rsx! {
-            div { class: "w-full h-32 bg-gray-200 rounded flex items-center justify-center",
-                svg {
-                    class: "w-8 h-8 text-gray-400",
-                    fill: "none",
-                    stroke: "currentColor",
-                    view_box: "0 0 24 24",
-                    path {
-                        stroke_linecap: "round",
-                        stroke_linejoin: "round",
-                        stroke_width: "2",
-                        d: "M4 16l4.586-4.586a2 2 0 012.828 0L16 16m-2-2l1.586-1.586a2 2 0 012.828 0L20 14m-6-6h.01M6 20h12a2 2 0 002-2V6a2 2 0 00-2-2H6a2 2 0 00-2 2v12a2 2 0 002 2z",
-                    }
-                }
-            }
+            CanvasImage { component: component.clone(), breakpoint }
         },
         ComponentType::Form => rsx! {
-            div { class: "p-4 border border-gray-200 rounded-lg",
+            div { class: canvas_class(component, "p-4 border border-gray-200 rounded-lg", breakpoint),
                 div { class: "mb-3",
                     label { class: "block text-sm font-medium mb-1", "Input Field" }
                     input {
@@ -423,13 +558,47 @@ fn render_column_component_content(component: &DraggableComponent) -> Element {
                         placeholder: "Enter text here...",
                     }
                 }
-                button { class: "px-4 py-2 bg-green-500 text-white rounded hover:bg-green-600",
+                button { class: "px-4 py-2 bg-[var(--theme-button)] text-white rounded hover:opacity-90",
                     "Submit"
                 }
             }
         },
         _ => rsx! {
-            div { class: "p-2 border border-gray-300 rounded text-gray-600", "{component.name}" }
+            div { class: canvas_class(component, "p-2 border border-gray-300 rounded text-gray-600", breakpoint), "{component.name}" }
+        },
+    }
+}
+
+/// An Image on the canvas: its asset when one is set, otherwise a placeholder.
+#[component]
+fn CanvasImage(component: DraggableComponent, breakpoint: Option<Breakpoint>) -> Element {
+    let drag_context = use_drag_drop_context();
+    let asset = component.properties.asset_id.as_deref().and_then(|asset_id| drag_context.get_asset(asset_id));
+
+    match asset {
+        Some(asset) => rsx! {
+            img {
+                class: canvas_class(&component, "w-full h-auto rounded", breakpoint),
+                src: "{asset.preview_url}",
+                alt: "{asset.name}",
+                draggable: "false",
+            }
+        },
+        None => rsx! {
+            div { class: canvas_class(&component, "w-full h-32 bg-gray-200 rounded flex items-center justify-center", breakpoint),
+                svg {
+                    class: "w-8 h-8 text-gray-400",
+                    fill: "none",
+                    stroke: "currentColor",
+                    view_box: "0 0 24 24",
+                    path {
+                        stroke_linecap: "round",
+                        stroke_linejoin: "round",
+                        stroke_width: "2",
+                        d: "M4 16l4.586-4.586a2 2 0 012.828 0L16 16m-2-2l1.586-1.586a2 2 0 012.828 0L20 14m-6-6h.01M6 20h12a2 2 0 002-2V6a2 2 0 00-2-2H6a2 2 0 00-2 2v12a2 2 0 002 2z",
+                    }
+                }
+            }
         },
     }
 }
@@ -0,0 +1,577 @@
+//! Turns the components on the canvas into standalone HTML or Dioxus RSX source. Everything
+//! taken from the document (names, content, classes, attributes) is escaped for the target.
+
+use super::drag_context::{column_children, Asset, ComponentType, Document, DraggableComponent, Page};
+use super::theme::{ColorScheme, ThemeSettings, ThemeToken};
+
+/// One generated file, ready to be saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportedFile {
+    pub file_name: String,
+    pub mime_type: &'static str,
+    pub contents: String,
+}
+
+/// Choices made in the export dialog that apply to every format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// Keep components hidden in the editor. Off drops them, and everything inside them.
+    pub include_hidden: bool,
+    /// Point images at their own URL instead of `assets/<file name>`: a data URI for files
+    /// kept in the project, the stable library URL for files stored on the server.
+    pub inline_assets: bool,
+    /// Add a `tailwind.config.js` and a `theme.css` holding the theme as named colors, and use
+    /// those names (`bg-primary`) so the pages can be built with purged CSS instead of the CDN.
+    pub tailwind_build: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { include_hidden: true, inline_assets: false, tailwind_build: false }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Html,
+    Rsx,
+}
+
+impl ExportFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "HTML",
+            ExportFormat::Rsx => "RSX",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "text/html",
+            ExportFormat::Rsx => "text/x-rust",
+        }
+    }
+
+    /// One file per page, named after the page's slug (`index` for the home page), followed by
+    /// the Tailwind build files when asked for.
+    pub fn export(&self, document: &Document, options: ExportOptions) -> Vec<ExportedFile> {
+        let mut files: Vec<ExportedFile> = document
+            .pages
+            .iter()
+            .map(|page| match self {
+                ExportFormat::Html => ExportedFile {
+                    file_name: format!("{}.html", page.file_stem()),
+                    mime_type: self.mime_type(),
+                    contents: to_html(document, page, options),
+                },
+                // Rust module names can't contain dashes
+                ExportFormat::Rsx => ExportedFile {
+                    file_name: format!("{}.rs", page.file_stem().replace('-', "_")),
+                    mime_type: self.mime_type(),
+                    contents: to_rsx(document, page, options),
+                },
+            })
+            .collect();
+        if options.tailwind_build {
+            files.push(ExportedFile {
+                file_name: "tailwind.config.js".into(),
+                mime_type: "text/javascript",
+                contents: tailwind_config(*self),
+            });
+            files.push(ExportedFile {
+                file_name: "theme.css".into(),
+                mime_type: "text/css",
+                contents: theme_stylesheet(&document.theme),
+            });
+        }
+        files
+    }
+}
+
+/// A Tailwind config with every theme token as a named color, read from the variables in
+/// [`theme_stylesheet`], so `bg-primary` follows the theme and `bg-primary-dark` its dark palette.
+pub fn tailwind_config(format: ExportFormat) -> String {
+    let content = match format {
+        ExportFormat::Html => "\"./*.html\"",
+        ExportFormat::Rsx => "\"./src/**/*.rs\"",
+    };
+    let colors: String = ThemeToken::ALL
+        .iter()
+        .map(|token| {
+            let variable = token.css_variable();
+            format!("        {}: {{ DEFAULT: \"var({variable})\", dark: \"var({variable}-dark)\" }},\n", token.name())
+        })
+        .collect();
+    format!("/** @type {{import('tailwindcss').Config}} */\nmodule.exports = {{\n  darkMode: \"media\",\n  content: [{content}],\n  theme: {{\n    extend: {{\n      colors: {{\n{colors}      }},\n    }},\n  }},\n  plugins: [],\n}};\n")
+}
+
+/// Tailwind's input stylesheet, with the theme's light and dark colors as `:root` variables.
+pub fn theme_stylesheet(theme: &ThemeSettings) -> String {
+    let dark = theme.scheme(ColorScheme::Dark);
+    let light_variables = ThemeToken::ALL
+        .iter()
+        .map(|&token| format!("  {}: {};\n", token.css_variable(), theme.css_value(token)));
+    let dark_variables = ThemeToken::ALL
+        .iter()
+        .map(|&token| format!("  {}-dark: {};\n", token.css_variable(), dark.css_value(token)));
+    let variables: String = light_variables.chain(dark_variables).collect();
+    format!("/* Build with: npx tailwindcss -i theme.css -o tailwind.css --minify */\n@tailwind base;\n@tailwind components;\n@tailwind utilities;\n\n:root {{\n{variables}}}\n")
+}
+
+/// `page` of `document` as a standalone HTML file.
+pub fn to_html(document: &Document, page: &Page, options: ExportOptions) -> String {
+    let components = canvas_components(page, options);
+    let context = ExportContext::new(&components, document, options);
+    // The stylesheet built from `theme.css`, or Tailwind's runtime from its CDN
+    let styles = if options.tailwind_build {
+        "<link rel=\"stylesheet\" href=\"tailwind.css\">"
+    } else {
+        "<script src=\"https://cdn.tailwindcss.com\"></script>"
+    };
+    let mut html = format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n    <title>{}</title>\n    {}\n</head>\n<body class=\"{}\">\n", escape_html(&page.name), styles, escape_html(&context.page_class()));
+
+    for component in components.iter().filter(|c| c.parent_id.is_none()) {
+        html.push_str(&component_to_html(component, &context, 4));
+    }
+
+    html.push_str("</body>\n</html>");
+    html
+}
+
+/// `page` of `document` as the body of a Dioxus component.
+pub fn to_rsx(document: &Document, page: &Page, options: ExportOptions) -> String {
+    let components = canvas_components(page, options);
+    let context = ExportContext::new(&components, document, options);
+    let mut rsx = format!("rsx! {{\n    div {{ class: \"min-h-screen {}\",\n", escape_rsx(&context.page_class()));
+
+    for component in components.iter().filter(|c| c.parent_id.is_none()) {
+        rsx.push_str(&component_to_rsx(component, &context, 2));
+    }
+
+    rsx.push_str("    }\n}");
+    rsx
+}
+
+/// Components placed on the canvas. Children of a dropped layout are never reached, since
+/// the layout itself is gone.
+fn canvas_components(page: &Page, options: ExportOptions) -> Vec<DraggableComponent> {
+    page.components
+        .iter()
+        .filter(|c| c.in_drop_zone && (options.include_hidden || !c.hidden))
+        .cloned()
+        .collect()
+}
+
+/// What every component of a page is exported against.
+struct ExportContext<'a> {
+    components: &'a [DraggableComponent],
+    assets: &'a [Asset],
+    theme: &'a ThemeSettings,
+    /// The theme with its dark palette's colors.
+    dark_theme: ThemeSettings,
+    options: ExportOptions,
+}
+
+impl<'a> ExportContext<'a> {
+    fn new(components: &'a [DraggableComponent], document: &'a Document, options: ExportOptions) -> Self {
+        Self {
+            components,
+            assets: &document.assets,
+            theme: &document.theme,
+            dark_theme: document.theme.scheme(ColorScheme::Dark),
+            options,
+        }
+    }
+
+    /// Classes setting `property` to the token's light and `dark:` colors: their names in the
+    /// Tailwind config when one is exported, the theme's colors otherwise.
+    fn token_class(&self, property: &str, token: ThemeToken) -> String {
+        if self.options.tailwind_build {
+            format!("{property}-{name} dark:{property}-{name}-dark", name = token.name())
+        } else {
+            format!("{} dark:{}", self.theme.class(property, token), self.dark_theme.class(property, token))
+        }
+    }
+
+    /// Page background and text color from the theme.
+    fn page_class(&self) -> String {
+        format!("{} {}", self.token_class("bg", ThemeToken::Background), self.token_class("text", ThemeToken::Text))
+    }
+
+    /// Classes used for every component type, with the theme's colors filled in.
+    fn base_class(&self, component_type: &ComponentType) -> String {
+        match component_type {
+            ComponentType::Header => format!("text-xl font-bold {}", self.token_class("text", ThemeToken::Text)),
+            ComponentType::Hero => format!(
+                "text-center p-4 bg-gradient-to-r {} {} text-white rounded",
+                self.token_class("from", ThemeToken::Primary),
+                self.token_class("to", ThemeToken::Accent),
+            ),
+            ComponentType::Text => self.token_class("text", ThemeToken::Text),
+            ComponentType::Button => format!("px-4 py-2 {} text-white rounded hover:opacity-90", self.token_class("bg", ThemeToken::Button)),
+            ComponentType::Card => format!("p-3 {} border border-gray-200 rounded-lg shadow-sm", self.token_class("bg", ThemeToken::Background)),
+            ComponentType::Footer => format!("p-2 {} text-white text-center rounded", self.token_class("bg", ThemeToken::Secondary)),
+            ComponentType::Image => "w-full h-auto rounded".to_string(),
+            _ => "p-2 border border-gray-300 rounded".to_string(),
+        }
+    }
+
+    /// The asset an Image component shows, with the `src` to export it under.
+    fn image_source(&self, component: &DraggableComponent) -> Option<(&Asset, String)> {
+        let asset_id = component.properties.asset_id.as_deref()?;
+        let asset = self.assets.iter().find(|asset| asset.id == asset_id)?;
+        // A `blob:` preview only works in the page that made it, so those are linked instead
+        let inline = self.options.inline_assets && !asset.preview_url.starts_with("blob:");
+        let src = if inline { asset.preview_url.clone() } else { asset.export_path() };
+        Some((asset, src))
+    }
+}
+
+/// Escapes text for use in HTML element content and double-quoted attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes text for an rsx string literal. Those are format strings, so braces are doubled as
+/// well as the usual Rust string escapes.
+pub fn escape_rsx(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '{' => escaped.push_str("{{"),
+            '}' => escaped.push_str("}}"),
+            c if c.is_control() => escaped.push_str(&c.escape_unicode().to_string()),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Attribute names can't be escaped, so names HTML would misread (spaces, quotes, `=`, `>`...)
+/// are left out of the export.
+fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| !c.is_whitespace() && !c.is_control() && !matches!(c, '"' | '\'' | '>' | '/' | '=' | '<'))
+}
+
+/// Classes of column `column_index` of a layout: the placeholder styling plus its span.
+fn column_class(layout: &DraggableComponent, column_index: usize) -> String {
+    let base = "min-h-32 border-2 border-dashed border-gray-300 p-2";
+    match layout.column_span_class(column_index) {
+        Some(span) => format!("{base} {span}"),
+        None => base.to_string(),
+    }
+}
+
+fn component_to_html(component: &DraggableComponent, context: &ExportContext, indent: usize) -> String {
+    let spaces = " ".repeat(indent);
+    let image = context.image_source(component);
+    let attributes: String = component
+        .sorted_attributes()
+        .into_iter()
+        .filter(|(key, _)| is_valid_attribute_name(key))
+        // The asset decides the picture
+        .filter(|(key, _)| image.is_none() || !matches!(key.as_str(), "src" | "alt"))
+        .map(|(key, value)| format!(" {}=\"{}\"", key, escape_html(value)))
+        .collect();
+    let class = |base: &str| escape_html(&component.class_list(base));
+    let base = context.base_class(&component.component_type);
+    let primary_text = escape_html(&context.token_class("text", ThemeToken::Primary));
+    let body_text = escape_html(&context.token_class("text", ThemeToken::Text));
+    let name = escape_html(&component.name);
+    let body = escape_html(component.body().unwrap_or_default());
+
+    if let (ComponentType::Image, Some((asset, src))) = (&component.component_type, &image) {
+        return format!("{}<img class=\"{}\" src=\"{}\" alt=\"{}\"{}>\n", spaces, class(&base), escape_html(src), escape_html(&asset.name), attributes);
+    }
+
+    match &component.component_type {
+        ComponentType::Grid => {
+            let mut html = format!("{}<div class=\"{}\"{}>\n", spaces, class(&format!("{} p-4", component.grid_class())), attributes);
+
+            for i in 0..component.columns.len() {
+                html.push_str(&format!("{}    <div class=\"{}\">\n", spaces, column_class(component, i)));
+
+                // Add children in this column, nested layouts included
+                for child in column_children(component, i, context.components) {
+                    html.push_str(&component_to_html(child, context, indent + 8));
+                }
+
+                html.push_str(&format!("{}    </div>\n", spaces));
+            }
+
+            html.push_str(&format!("{}</div>\n", spaces));
+            html
+        },
+        ComponentType::Header => format!("{}<h1 class=\"{}\"{}>{}</h1>\n", spaces, class(&base), attributes, name),
+        ComponentType::Hero => format!("{}<div class=\"{}\"{}>\n{}    <h2 class=\"text-lg font-bold\">{}</h2>\n{}    <p class=\"text-sm\">{}</p>\n{}</div>\n", spaces, class(&base), attributes, spaces, name, spaces, body, spaces),
+        ComponentType::Text => format!("{}<p class=\"{}\"{}>{}</p>\n", spaces, class(&base), attributes, name),
+        ComponentType::Button => format!("{}<button class=\"{}\"{}>{}</button>\n", spaces, class(&base), attributes, name),
+        ComponentType::Card => format!("{}<div class=\"{}\"{}>\n{}    <h3 class=\"font-semibold {}\">{}</h3>\n{}    <p class=\"text-sm {}\">{}</p>\n{}</div>\n", spaces, class(&base), attributes, spaces, primary_text, name, spaces, body_text, body, spaces),
+        ComponentType::Footer => format!("{}<div class=\"{}\"{}>\n{}    <p class=\"text-sm\">{}</p>\n{}</div>\n", spaces, class(&base), attributes, spaces, name, spaces),
+        _ => format!("{}<div class=\"{}\"{}>{}</div>\n", spaces, class(&base), attributes, name),
+    }
+}
+
+fn component_to_rsx(component: &DraggableComponent, context: &ExportContext, indent: usize) -> String {
+    let spaces = " ".repeat(indent);
+    let image = context.image_source(component);
+    // Quoted names so arbitrary attributes like `data-*` stay valid rsx
+    let attributes: String = component
+        .sorted_attributes()
+        .into_iter()
+        .filter(|(key, _)| is_valid_attribute_name(key))
+        .filter(|(key, _)| image.is_none() || !matches!(key.as_str(), "src" | "alt"))
+        .map(|(key, value)| format!(" \"{}\": \"{}\",", escape_rsx(key), escape_rsx(value)))
+        .collect();
+    let class = |base: &str| escape_rsx(&component.class_list(base));
+    let base = context.base_class(&component.component_type);
+    let primary_text = escape_rsx(&context.token_class("text", ThemeToken::Primary));
+    let body_text = escape_rsx(&context.token_class("text", ThemeToken::Text));
+    let name = escape_rsx(&component.name);
+    let body = escape_rsx(component.body().unwrap_or_default());
+
+    if let (ComponentType::Image, Some((asset, src))) = (&component.component_type, &image) {
+        return format!("{}img {{ class: \"{}\", src: \"{}\", alt: \"{}\",{} }}\n", spaces, class(&base), escape_rsx(src), escape_rsx(&asset.name), attributes);
+    }
+
+    match &component.component_type {
+        ComponentType::Grid => {
+            let mut rsx = format!("{}div {{ class: \"{}\",{}\n", spaces, class(&format!("{} p-4", component.grid_class())), attributes);
+
+            for i in 0..component.columns.len() {
+                rsx.push_str(&format!("{}    div {{ class: \"{}\",\n", spaces, column_class(component, i)));
+
+                // Add children in this column, nested layouts included
+                for child in column_children(component, i, context.components) {
+                    rsx.push_str(&component_to_rsx(child, context, indent + 8));
+                }
+
+                rsx.push_str(&format!("{}    }}\n", spaces));
+            }
+
+            rsx.push_str(&format!("{}}}\n", spaces));
+            rsx
+        },
+        ComponentType::Header => format!("{}h1 {{ class: \"{}\",{} \"{}\" }}\n", spaces, class(&base), attributes, name),
+        ComponentType::Hero => format!("{}div {{ class: \"{}\",{}\n{}    h2 {{ class: \"text-lg font-bold\", \"{}\" }}\n{}    p {{ class: \"text-sm\", \"{}\" }}\n{}}}\n", spaces, class(&base), attributes, spaces, name, spaces, body, spaces),
+        ComponentType::Text => format!("{}p {{ class: \"{}\",{} \"{}\" }}\n", spaces, class(&base), attributes, name),
+        ComponentType::Button => format!("{}button {{ class: \"{}\",{} \"{}\" }}\n", spaces, class(&base), attributes, name),
+        ComponentType::Card => format!("{}div {{ class: \"{}\",{}\n{}    h3 {{ class: \"font-semibold {}\", \"{}\" }}\n{}    p {{ class: \"text-sm {}\", \"{}\" }}\n{}}}\n", spaces, class(&base), attributes, spaces, primary_text, name, spaces, body_text, body, spaces),
+        ComponentType::Footer => format!("{}div {{ class: \"{}\",{}\n{}    p {{ class: \"text-sm\", \"{}\" }}\n{}}}\n", spaces, class(&base), attributes, spaces, name, spaces),
+        _ => format!("{}div {{ class: \"{}\",{} \"{}\" }}\n", spaces, class(&base), attributes, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use scraper::{Html, Selector};
+
+    use super::*;
+
+    /// A document with one Card on the canvas carrying the given name, body and attribute.
+    fn card_document(name: &str, body: &str, attribute_value: &str) -> Document {
+        let mut document = Document::new();
+        let id = document.add_component(ComponentType::Card);
+        document.move_to_drop_zone(&id, (0.0, 0.0));
+        document.update_component_name(&id, name);
+        document.update_component_content(&id, body);
+        document.set_component_attribute(&id, "title", attribute_value);
+        document
+    }
+
+    /// Undoes [`escape_rsx`], the way the Rust lexer and `format!` would.
+    fn unescape_rsx(text: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek().copied()) {
+                ('\\', Some('n')) => { chars.next(); unescaped.push('\n'); }
+                ('\\', Some('r')) => { chars.next(); unescaped.push('\r'); }
+                ('\\', Some('t')) => { chars.next(); unescaped.push('\t'); }
+                ('\\', Some('u')) => {
+                    chars.next();
+                    let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                    unescaped.push(char::from_u32(u32::from_str_radix(&code, 16).unwrap()).unwrap());
+                }
+                ('\\', Some(next)) | ('{', Some(next @ '{')) | ('}', Some(next @ '}')) => {
+                    chars.next();
+                    unescaped.push(next);
+                }
+                _ => unescaped.push(c),
+            }
+        }
+        unescaped
+    }
+
+    proptest! {
+        #[test]
+        fn html_export_parses_back_to_the_same_text(
+            name in "[^\u{0}\r]*",
+            body in "[^\u{0}\r]*",
+            title in "[^\u{0}\r]*",
+        ) {
+            // Empty content falls back to the card's placeholder body
+            prop_assume!(!body.is_empty());
+            let document = card_document(&name, &body, &title);
+            let html = Html::parse_document(&to_html(&document, document.page(), ExportOptions::default()));
+
+            let heading = html.select(&Selector::parse("h3").unwrap()).next().unwrap();
+            let paragraph = html.select(&Selector::parse("h3 + p").unwrap()).next().unwrap();
+            let card = html.select(&Selector::parse("body > div").unwrap()).next().unwrap();
+            prop_assert_eq!(heading.text().collect::<String>(), name);
+            prop_assert_eq!(paragraph.text().collect::<String>(), body);
+            prop_assert_eq!(card.value().attr("title"), Some(title.as_str()));
+        }
+
+        #[test]
+        fn rsx_strings_unescape_to_the_same_text(text in any::<String>()) {
+            let escaped = escape_rsx(&text);
+            // Nothing can end the string literal or open an interpolation early
+            let unquoted = escaped.replace("\\\\", "").replace("\\\"", "");
+            prop_assert!(!unquoted.contains('"'), "unescaped quote in {}", escaped);
+            let unbraced = escaped.replace("{{", "");
+            prop_assert!(!unbraced.contains('{'), "unescaped brace in {}", escaped);
+            prop_assert_eq!(unescape_rsx(&escaped), text);
+        }
+    }
+
+    #[test]
+    fn attributes_with_unsafe_names_are_left_out() {
+        let mut document = card_document("Card", "Body", "ok");
+        let id = document.get_components_in_drop_zone()[0].id.clone();
+        document.set_component_attribute(&id, "onclick=\"alert(1)\"", "x");
+
+        let html = to_html(&document, document.page(), ExportOptions::default());
+        assert!(html.contains(" title=\"ok\""));
+        assert!(!html.contains("alert"));
+    }
+
+    #[test]
+    fn hidden_components_are_kept_unless_left_out() {
+        let mut document = card_document("Hidden card", "Body", "ok");
+        let id = document.get_components_in_drop_zone()[0].id.clone();
+        document.set_component_hidden(&id, true);
+
+        let kept = to_html(&document, document.page(), ExportOptions::default());
+        assert!(kept.contains("Hidden card"));
+        let dropped = to_rsx(&document, document.page(), ExportOptions { include_hidden: false, ..ExportOptions::default() });
+        assert!(!dropped.contains("Hidden card"));
+    }
+
+    #[test]
+    fn nested_layouts_export_at_every_depth() {
+        let mut document = Document::new();
+        let outer = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&outer, (0.0, 0.0));
+        let inner = document.add_component(ComponentType::Grid);
+        document.set_grid_column_count(&inner, 1);
+        document.add_to_column(&inner, &outer, 1);
+        let text = document.add_component(ComponentType::Text);
+        document.add_to_column(&text, &inner, 0);
+        document.update_component_name(&text, "Deep text");
+
+        let html = Html::parse_document(&to_html(&document, document.page(), ExportOptions::default()));
+        let selector = Selector::parse("body > div > div:nth-child(2) > div > div > p").unwrap();
+        let paragraph = html.select(&selector).next().expect("text inside the nested layout");
+        assert_eq!(paragraph.text().collect::<String>(), "Deep text");
+
+        let rsx = to_rsx(&document, document.page(), ExportOptions::default());
+        assert!(rsx.contains(&format!("{}p {{ class: \"text-gray-800 dark:text-gray-100\", \"Deep text\" }}", " ".repeat(18))));
+    }
+
+    #[test]
+    fn images_link_to_their_asset_or_inline_it() {
+        let mut document = Document::new();
+        let grid = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&grid, (0.0, 0.0));
+        document.add_asset(Asset {
+            id: "asset_1".into(),
+            name: "team photo.png".into(),
+            file_type: "image/png".into(),
+            preview_url: "data:image/png;base64,AAAA".into(),
+            size: "3 B".into(),
+        });
+        document.insert_image_into_column("asset_1", &grid, 0, 0);
+
+        let linked = to_html(&document, document.page(), ExportOptions::default());
+        assert!(linked.contains("<img class=\"w-full h-auto rounded\" src=\"assets/team-photo.png\" alt=\"team photo.png\">"));
+        let inlined = to_rsx(&document, document.page(), ExportOptions { inline_assets: true, ..ExportOptions::default() });
+        assert!(inlined.contains("src: \"data:image/png;base64,AAAA\""));
+    }
+
+    #[test]
+    fn theme_colors_reach_every_button_and_hero() {
+        let mut document = Document::new();
+        let grid = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&grid, (0.0, 0.0));
+        for (column, component_type) in [ComponentType::Button, ComponentType::Hero].into_iter().enumerate() {
+            let id = document.add_component(component_type);
+            document.add_to_column(&id, &grid, column);
+        }
+        document.theme.primary_color = "emerald-600".into();
+        document.theme.accent_color = "#1e40af".into();
+
+        let html = Html::parse_document(&to_html(&document, document.page(), ExportOptions::default()));
+        let button = html.select(&Selector::parse("button").unwrap()).next().unwrap();
+        assert!(button.value().classes().any(|class| class == "bg-emerald-600"));
+        // Buttons follow the dark palette's own primary color in dark mode
+        assert!(button.value().classes().any(|class| class == "dark:bg-blue-600"));
+        let hero = html.select(&Selector::parse("h2").unwrap()).next().unwrap().parent().unwrap();
+        let hero_class = hero.value().as_element().unwrap().attr("class").unwrap();
+        assert!(hero_class.contains("from-emerald-600 dark:from-blue-600 to-[#1e40af] dark:to-indigo-500"));
+    }
+
+    #[test]
+    fn tailwind_build_names_the_theme_colors() {
+        let mut document = card_document("Card", "Body", "ok");
+        document.theme.primary_color = "rose-500".into();
+        let options = ExportOptions { tailwind_build: true, ..ExportOptions::default() };
+
+        let files = ExportFormat::Html.export(&document, options);
+        let names: Vec<&str> = files.iter().map(|file| file.file_name.as_str()).collect();
+        assert_eq!(names, ["index.html", "tailwind.config.js", "theme.css"]);
+        assert!(files[0].contents.contains("<link rel=\"stylesheet\" href=\"tailwind.css\">"));
+        assert!(files[0].contents.contains("<h3 class=\"font-semibold text-primary dark:text-primary-dark\">"));
+        assert!(files[1].contents.contains("primary: { DEFAULT: \"var(--theme-primary)\", dark: \"var(--theme-primary-dark)\" },"));
+        assert!(files[2].contents.contains("  --theme-primary: #f43f5e;\n  --theme-secondary: #4b5563;\n"));
+        assert!(files[2].contents.contains("  --theme-primary-dark: #2563eb;\n"));
+    }
+
+    #[test]
+    fn theme_colors_are_escaped() {
+        // Loading a project rejects colors like this one, but the export mustn't rely on it
+        let mut document = card_document("Card", "Body", "ok");
+        document.theme.primary_color = "x\" onload=\"alert(1)".into();
+        document.theme.text_color = "x\", onclick: \"{alert(1)}".into();
+
+        let html = to_html(&document, document.page(), ExportOptions::default());
+        assert!(!html.contains("\" onload="), "{html}");
+        let h3 = Html::parse_document(&html);
+        let title = h3.select(&Selector::parse("h3").unwrap()).next().unwrap();
+        assert_eq!(title.value().attrs().count(), 1);
+
+        let rsx = to_rsx(&document, document.page(), ExportOptions::default());
+        assert!(!rsx.contains("\", onclick: \"{alert(1)}"), "{rsx}");
+        assert!(rsx.contains(&escape_rsx("text-[x\", onclick: \"{alert(1)}]")));
+    }
+}
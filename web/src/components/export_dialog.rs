@@ -0,0 +1,297 @@
+use dioxus::prelude::*;
+use crate::components::{copy_to_clipboard, download_file, download_files, use_drag_drop_context, ExportFormat, ExportOptions};
+
+// Preview colors, roughly a dark editor theme
+const TAG: &str = "text-pink-400";
+const ATTRIBUTE: &str = "text-sky-300";
+const STRING: &str = "text-amber-300";
+const PUNCTUATION: &str = "text-gray-500";
+const PLAIN: &str = "text-gray-100";
+
+/// A run of source text and the class it is colored with.
+type Token = (&'static str, String);
+
+/// Modal with a highlighted preview of the export, plus copy and download actions.
+#[component]
+pub fn ExportDialog(initial_format: ExportFormat, on_close: EventHandler) -> Element {
+    let drag_context = use_drag_drop_context();
+    let mut format = use_signal(|| initial_format);
+    let mut file_index = use_signal(|| 0);
+    let mut options = use_signal(ExportOptions::default);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    // One file per page, plus the Tailwind build files; the preview shows the one picked in the
+    // file list
+    let files = drag_context.export(format(), options());
+    let file = files[file_index().min(files.len() - 1)].clone();
+    let tokens = if file.file_name.ends_with(".html") {
+        highlight_html(&file.contents)
+    } else {
+        highlight_rsx(&file.contents)
+    };
+
+    let copy = {
+        let contents = file.contents.clone();
+        move |_| {
+            let contents = contents.clone();
+            async move {
+                match copy_to_clipboard(&contents).await {
+                    Ok(()) => status.set(Some(Ok("Copied to clipboard".into()))),
+                    Err(err) => status.set(Some(Err(err))),
+                }
+            }
+        }
+    };
+    let download = {
+        let file = file.clone();
+        move |_| {
+            let file = file.clone();
+            async move {
+                match download_file(&file.file_name, file.mime_type, &file.contents).await {
+                    Ok(true) => status.set(Some(Ok(format!("Saved {}", file.file_name)))),
+                    Ok(false) => {}
+                    Err(err) => status.set(Some(Err(err))),
+                }
+            }
+        }
+    };
+    let download_all = {
+        let files = files.clone();
+        move |_| {
+            let files = files.clone();
+            async move {
+                match download_files(&files).await {
+                    Ok(true) => status.set(Some(Ok(format!("Saved {} files", files.len())))),
+                    Ok(false) => {}
+                    Err(err) => status.set(Some(Err(err))),
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+            onclick: move |_| on_close.call(()),
+            div {
+                class: "w-full max-w-4xl bg-white rounded-lg shadow-xl flex flex-col max-h-full",
+                onclick: move |e| e.stop_propagation(),
+                // Header
+                div { class: "flex justify-between items-center p-4 border-b border-gray-200",
+                    div { class: "flex items-center gap-4",
+                        h2 { class: "text-lg font-semibold", "Export" }
+                        div { class: "flex rounded-lg border border-gray-300 overflow-hidden text-sm",
+                            for option in [ExportFormat::Html, ExportFormat::Rsx] {
+                                button {
+                                    class: if format() == option { "px-3 py-1 bg-blue-600 text-white" } else { "px-3 py-1 bg-white text-gray-700 hover:bg-gray-100" },
+                                    onclick: move |_| {
+                                        format.set(option);
+                                        status.set(None);
+                                    },
+                                    "{option.label()}"
+                                }
+                            }
+                        }
+                    }
+                    button {
+                        class: "p-1 rounded hover:bg-gray-100 text-gray-500",
+                        onclick: move |_| on_close.call(()),
+                        "×"
+                    }
+                }
+                // Files
+                if files.len() > 1 {
+                    div { class: "flex flex-wrap gap-1 px-4 pt-4",
+                        for (index, page_file) in files.iter().enumerate() {
+                            button {
+                                key: "{page_file.file_name}",
+                                class: if index == file_index().min(files.len() - 1) { "px-2 py-1 rounded bg-gray-800 text-white font-mono text-xs" } else { "px-2 py-1 rounded bg-gray-100 text-gray-700 hover:bg-gray-200 font-mono text-xs" },
+                                onclick: move |_| {
+                                    file_index.set(index);
+                                    status.set(None);
+                                },
+                                "{page_file.file_name}"
+                            }
+                        }
+                    }
+                }
+                // Preview
+                pre { class: "flex-1 min-h-0 overflow-auto m-4 p-4 bg-gray-900 rounded-lg font-mono text-xs leading-relaxed",
+                    code {
+                        for (class, text) in tokens {
+                            span { class, "{text}" }
+                        }
+                    }
+                }
+                // Actions
+                div { class: "flex justify-between items-center p-4 border-t border-gray-200",
+                    match status() {
+                        Some(Ok(message)) => rsx! {
+                            p { class: "text-sm text-green-600", "{message}" }
+                        },
+                        Some(Err(message)) => rsx! {
+                            p { class: "text-sm text-red-600", "{message}" }
+                        },
+                        None => rsx! {
+                            p { class: "text-sm text-gray-500", "{file.file_name}" }
+                        },
+                    }
+                    div { class: "flex items-center gap-2",
+                        label { class: "flex items-center gap-2 mr-2 text-sm text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: options().include_hidden,
+                                onchange: move |e| options.write().include_hidden = e.checked(),
+                            }
+                            "Include hidden layers"
+                        }
+                        label {
+                            class: "flex items-center gap-2 mr-2 text-sm text-gray-700",
+                            title: "Use each image's library URL, or embed it, instead of linking to assets/",
+                            input {
+                                r#type: "checkbox",
+                                checked: options().inline_assets,
+                                onchange: move |e| options.write().inline_assets = e.checked(),
+                            }
+                            "Inline images"
+                        }
+                        label {
+                            class: "flex items-center gap-2 mr-2 text-sm text-gray-700",
+                            title: "Add tailwind.config.js and theme.css to build purged CSS instead of loading Tailwind from its CDN",
+                            input {
+                                r#type: "checkbox",
+                                checked: options().tailwind_build,
+                                onchange: move |e| options.write().tailwind_build = e.checked(),
+                            }
+                            "Tailwind build files"
+                        }
+                        button {
+                            class: "px-3 py-2 border border-gray-300 rounded hover:bg-gray-50 text-sm",
+                            onclick: copy,
+                            "Copy to clipboard"
+                        }
+                        button {
+                            class: "px-3 py-2 bg-blue-600 text-white rounded hover:bg-blue-700 text-sm",
+                            onclick: download,
+                            "Download"
+                        }
+                        if files.len() > 1 {
+                            button {
+                                class: "px-3 py-2 bg-blue-600 text-white rounded hover:bg-blue-700 text-sm",
+                                onclick: download_all,
+                                "Download all files"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Appends `text`, merging it into the previous token when the colors match.
+fn push(tokens: &mut Vec<Token>, class: &'static str, text: &str) {
+    match tokens.last_mut() {
+        Some((last_class, last_text)) if *last_class == class => last_text.push_str(text),
+        _ => tokens.push((class, text.to_string())),
+    }
+}
+
+/// Length of the double-quoted string at the start of `text`, closing quote included.
+fn string_len(text: &str, backslash_escapes: bool) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            '\\' if backslash_escapes && !escaped => escaped = true,
+            '"' if !escaped => return i + 1,
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
+
+fn highlight_html(code: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = code;
+    let mut in_tag = false;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '<' if !in_tag => {
+                in_tag = true;
+                let len = rest.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(rest.len());
+                push(&mut tokens, PUNCTUATION, "<");
+                push(&mut tokens, TAG, &rest[1..len]);
+                len
+            }
+            _ if !in_tag => {
+                let len = rest.find('<').unwrap_or(rest.len());
+                push(&mut tokens, PLAIN, &rest[..len]);
+                len
+            }
+            '>' => {
+                in_tag = false;
+                push(&mut tokens, PUNCTUATION, ">");
+                1
+            }
+            '"' => {
+                let len = string_len(rest, false);
+                push(&mut tokens, STRING, &rest[..len]);
+                len
+            }
+            '=' | '/' => {
+                push(&mut tokens, PUNCTUATION, &rest[..1]);
+                1
+            }
+            c if c.is_whitespace() => {
+                push(&mut tokens, PLAIN, &rest[..c.len_utf8()]);
+                c.len_utf8()
+            }
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+                    .unwrap_or(rest.len());
+                push(&mut tokens, ATTRIBUTE, &rest[..len]);
+                len
+            }
+        };
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+fn highlight_rsx(code: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '"' => {
+                let len = string_len(rest, true);
+                push(&mut tokens, STRING, &rest[..len]);
+                len
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                // Elements and macros are followed by `{` or `!`, attributes by `:`
+                let class = match rest[len..].trim_start().chars().next() {
+                    Some('{' | '!') => TAG,
+                    Some(':') => ATTRIBUTE,
+                    _ => PLAIN,
+                };
+                push(&mut tokens, class, &rest[..len]);
+                len
+            }
+            '{' | '}' | ':' | ',' | '!' => {
+                push(&mut tokens, PUNCTUATION, &rest[..1]);
+                1
+            }
+            c => {
+                push(&mut tokens, PLAIN, &rest[..c.len_utf8()]);
+                c.len_utf8()
+            }
+        };
+        rest = &rest[len..];
+    }
+    tokens
+}
@@ -0,0 +1,120 @@
+//! Settings of the grid layout component and the Tailwind classes they turn into.
+//!
+//! Tailwind only generates classes it finds spelled out in the source, so every class the
+//! canvas can produce is listed literally in the tables below rather than built with
+//! `format!`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::breakpoint::Breakpoint;
+
+/// Most tracks, and the widest span, a grid can have.
+pub const MAX_COLUMNS: usize = 12;
+
+/// Tailwind spacing steps offered for the gap between columns.
+pub const GAPS: [usize; 7] = [0, 1, 2, 4, 6, 8, 12];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridSettings {
+    /// Tracks on the smallest screens. `None` gives every column its own track.
+    pub columns: Option<usize>,
+    /// Track counts from a breakpoint up, overriding the smaller sizes.
+    pub breakpoint_columns: BTreeMap<Breakpoint, usize>,
+    /// Tailwind spacing step between columns (`gap-4`).
+    pub gap: usize,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            breakpoint_columns: BTreeMap::new(),
+            gap: 4,
+        }
+    }
+}
+
+impl GridSettings {
+    /// Tracks at `breakpoint` (`None` for the smallest screens), for a grid of `column_count`
+    /// columns.
+    pub fn columns_at(&self, breakpoint: Option<Breakpoint>, column_count: usize) -> usize {
+        let base = self.columns.unwrap_or(column_count);
+        Breakpoint::ALL
+            .iter()
+            .take_while(|&&smaller| Some(smaller) <= breakpoint)
+            .filter_map(|smaller| self.breakpoint_columns.get(smaller).copied())
+            .last()
+            .unwrap_or(base)
+            .clamp(1, MAX_COLUMNS)
+    }
+
+    /// Classes for the grid container, e.g. `grid grid-cols-1 md:grid-cols-3 gap-4`.
+    pub fn class(&self, column_count: usize) -> String {
+        let mut classes = vec!["grid", GRID_COLS[0][self.columns_at(None, column_count) - 1]];
+        for (breakpoint, &columns) in &self.breakpoint_columns {
+            classes.push(GRID_COLS[*breakpoint as usize + 1][columns.clamp(1, MAX_COLUMNS) - 1]);
+        }
+        classes.push(gap_class(self.gap));
+        classes.join(" ")
+    }
+
+    /// Span class for a column spanning `span` tracks, or `None` for a single track. It only
+    /// starts at the first size with enough tracks, so narrow screens don't sprout extra ones.
+    pub fn span_class(&self, span: usize, column_count: usize) -> Option<&'static str> {
+        let span = span.clamp(1, MAX_COLUMNS);
+        if span == 1 {
+            return None;
+        }
+        std::iter::once(None)
+            .chain(Breakpoint::ALL.map(Some))
+            .position(|breakpoint| self.columns_at(breakpoint, column_count) >= span)
+            .map(|size| COL_SPAN[size][span - 1])
+    }
+}
+
+fn gap_class(gap: usize) -> &'static str {
+    match gap {
+        0 => "gap-0",
+        1 => "gap-1",
+        2 => "gap-2",
+        6 => "gap-6",
+        8 => "gap-8",
+        12 => "gap-12",
+        _ => "gap-4",
+    }
+}
+
+/// `grid-cols-N`, indexed by size (base, `sm`, `md`, `lg`) and then `N - 1`.
+const GRID_COLS: [[&str; MAX_COLUMNS]; 4] = [
+    ["grid-cols-1", "grid-cols-2", "grid-cols-3", "grid-cols-4", "grid-cols-5", "grid-cols-6", "grid-cols-7", "grid-cols-8", "grid-cols-9", "grid-cols-10", "grid-cols-11", "grid-cols-12"],
+    ["sm:grid-cols-1", "sm:grid-cols-2", "sm:grid-cols-3", "sm:grid-cols-4", "sm:grid-cols-5", "sm:grid-cols-6", "sm:grid-cols-7", "sm:grid-cols-8", "sm:grid-cols-9", "sm:grid-cols-10", "sm:grid-cols-11", "sm:grid-cols-12"],
+    ["md:grid-cols-1", "md:grid-cols-2", "md:grid-cols-3", "md:grid-cols-4", "md:grid-cols-5", "md:grid-cols-6", "md:grid-cols-7", "md:grid-cols-8", "md:grid-cols-9", "md:grid-cols-10", "md:grid-cols-11", "md:grid-cols-12"],
+    ["lg:grid-cols-1", "lg:grid-cols-2", "lg:grid-cols-3", "lg:grid-cols-4", "lg:grid-cols-5", "lg:grid-cols-6", "lg:grid-cols-7", "lg:grid-cols-8", "lg:grid-cols-9", "lg:grid-cols-10", "lg:grid-cols-11", "lg:grid-cols-12"],
+];
+
+/// `col-span-N`, indexed like [`GRID_COLS`].
+const COL_SPAN: [[&str; MAX_COLUMNS]; 4] = [
+    ["col-span-1", "col-span-2", "col-span-3", "col-span-4", "col-span-5", "col-span-6", "col-span-7", "col-span-8", "col-span-9", "col-span-10", "col-span-11", "col-span-12"],
+    ["sm:col-span-1", "sm:col-span-2", "sm:col-span-3", "sm:col-span-4", "sm:col-span-5", "sm:col-span-6", "sm:col-span-7", "sm:col-span-8", "sm:col-span-9", "sm:col-span-10", "sm:col-span-11", "sm:col-span-12"],
+    ["md:col-span-1", "md:col-span-2", "md:col-span-3", "md:col-span-4", "md:col-span-5", "md:col-span-6", "md:col-span-7", "md:col-span-8", "md:col-span-9", "md:col-span-10", "md:col-span-11", "md:col-span-12"],
+    ["lg:col-span-1", "lg:col-span-2", "lg:col-span-3", "lg:col-span-4", "lg:col-span-5", "lg:col-span-6", "lg:col-span-7", "lg:col-span-8", "lg:col-span-9", "lg:col-span-10", "lg:col-span-11", "lg:col-span-12"],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_start_at_the_first_size_with_enough_tracks() {
+        let mut grid = GridSettings { columns: Some(1), ..GridSettings::default() };
+        grid.breakpoint_columns.insert(Breakpoint::Md, 3);
+
+        assert_eq!(grid.class(2), "grid grid-cols-1 md:grid-cols-3 gap-4");
+        assert_eq!(grid.span_class(2, 2), Some("md:col-span-2"));
+        assert_eq!(grid.span_class(1, 2), None);
+        assert_eq!(GridSettings::default().span_class(2, 2), Some("col-span-2"));
+    }
+}
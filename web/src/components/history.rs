@@ -0,0 +1,442 @@
+use std::collections::VecDeque;
+
+use super::drag_context::{Asset, Document, Page};
+use super::theme::ThemeSettings;
+
+/// How many steps the builder remembers before dropping the oldest ones.
+const HISTORY_LIMIT: usize = 100;
+
+/// A single mutation applied through the [`DragDropContext`](super::DragDropContext).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    AddComponent { component_id: String },
+    MoveToDropZone { component_id: String },
+    InsertIntoColumn { component_id: String, column_id: String },
+    MoveToRootIndex { component_id: String },
+    UpdateComponentName { component_id: String },
+    UpdateComponentContent { component_id: String },
+    UpdateComponentStyles { component_id: String },
+    SetComponentAttribute { component_id: String, key: String },
+    RemoveComponentAttribute { component_id: String, key: String },
+    RemoveComponent { component_id: String },
+    SetComponentHidden { component_id: String },
+    SetComponentLocked { component_id: String },
+    UpdateGrid { component_id: String },
+    SetComponentAsset { component_id: String },
+    UpdateTheme { field: &'static str },
+    AddAsset { asset_id: String },
+    UpdateAsset { asset_id: String },
+    RemoveAsset { asset_id: String },
+    AddPage { page_id: String },
+    DuplicatePage { page_id: String },
+    RenamePage { page_id: String },
+    SetPageSlug { page_id: String },
+    MovePage { page_id: String },
+    RemovePage { page_id: String },
+    /// The whole document was swapped out, e.g. by loading a project.
+    ReplaceDocument,
+}
+
+impl Command {
+    fn component_id(&self) -> Option<&str> {
+        let component_id = match self {
+            Command::AddComponent { component_id }
+            | Command::MoveToDropZone { component_id }
+            | Command::InsertIntoColumn { component_id, .. }
+            | Command::MoveToRootIndex { component_id }
+            | Command::UpdateComponentName { component_id }
+            | Command::UpdateComponentContent { component_id }
+            | Command::UpdateComponentStyles { component_id }
+            | Command::SetComponentAttribute { component_id, .. }
+            | Command::RemoveComponentAttribute { component_id, .. }
+            | Command::RemoveComponent { component_id }
+            | Command::SetComponentHidden { component_id }
+            | Command::SetComponentLocked { component_id }
+            | Command::UpdateGrid { component_id }
+            | Command::SetComponentAsset { component_id } => component_id,
+            _ => return None,
+        };
+        Some(component_id)
+    }
+
+    /// Whether `next` should be folded into `self` instead of becoming its own step.
+    ///
+    /// Consecutive edits to the same component's text, the same page's name or slug, or the
+    /// same theme field are one step, and so is creating a component from the palette and
+    /// placing it.
+    fn absorbs(&self, next: &Command) -> bool {
+        match (self, next) {
+            (Command::UpdateTheme { field }, Command::UpdateTheme { field: next_field }) => return field == next_field,
+            (Command::RenamePage { page_id }, Command::RenamePage { page_id: next_page_id })
+            | (Command::SetPageSlug { page_id }, Command::SetPageSlug { page_id: next_page_id }) => {
+                return page_id == next_page_id
+            }
+            _ => {}
+        }
+        if self.component_id().is_none() || self.component_id() != next.component_id() {
+            return false;
+        }
+        match (self, next) {
+            (
+                Command::SetComponentAttribute { key, .. },
+                Command::SetComponentAttribute { key: next_key, .. },
+            ) => key == next_key,
+            _ => matches!(
+                (self, next),
+                (Command::UpdateComponentName { .. }, Command::UpdateComponentName { .. })
+                    | (Command::UpdateComponentContent { .. }, Command::UpdateComponentContent { .. })
+                    | (Command::AddComponent { .. }, Command::MoveToDropZone { .. })
+                    | (Command::AddComponent { .. }, Command::InsertIntoColumn { .. })
+                    | (Command::AddComponent { .. }, Command::MoveToRootIndex { .. })
+            ),
+        }
+    }
+}
+
+/// The parts of a document that one step changed, as they are on one side of the step.
+///
+/// Pages, theme and assets the step left alone are not stored, so a text edit doesn't copy
+/// every other page or the asset list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch {
+    /// Pages that differ, by position.
+    pages: Vec<(usize, Page)>,
+    page_count: usize,
+    active_page: String,
+    next_id: usize,
+    theme: Option<ThemeSettings>,
+    assets: Option<Vec<Asset>>,
+}
+
+impl Patch {
+    /// Copies the parts of `document` that `command` can change, before it runs. Component
+    /// edits only touch the active page, so the other pages, theme and assets stay uncopied.
+    pub fn capture(document: &Document, command: &Command) -> Self {
+        let page_index = |page_id: &str| document.pages.iter().position(|page| page.id == page_id);
+        let (pages, theme, assets) = match command {
+            Command::UpdateTheme { .. } => (Vec::new(), true, false),
+            Command::AddAsset { .. } | Command::UpdateAsset { .. } => (Vec::new(), false, true),
+            Command::RenamePage { page_id } | Command::SetPageSlug { page_id } => {
+                (page_index(page_id).into_iter().collect(), false, false)
+            }
+            Command::AddPage { .. }
+            | Command::DuplicatePage { .. }
+            | Command::MovePage { .. }
+            | Command::RemovePage { .. } => ((0..document.pages.len()).collect(), false, false),
+            // Clears the asset from components on every page
+            Command::RemoveAsset { .. } => ((0..document.pages.len()).collect(), false, true),
+            Command::ReplaceDocument => ((0..document.pages.len()).collect(), true, true),
+            // Falls back to the first page, as the document's own lookups do
+            _ => {
+                let active = page_index(&document.active_page).or((!document.pages.is_empty()).then_some(0));
+                (active.into_iter().collect(), false, false)
+            }
+        };
+        Self {
+            pages: pages.into_iter().map(|index| (index, document.pages[index].clone())).collect(),
+            page_count: document.pages.len(),
+            active_page: document.active_page.clone(),
+            next_id: document.next_id,
+            theme: theme.then(|| document.theme.clone()),
+            assets: assets.then(|| document.assets.clone()),
+        }
+    }
+
+    /// Splits a capture taken before a step into the undo and redo patches for `after`, or
+    /// `None` if the step changed nothing.
+    fn diff(self, after: &Document) -> Option<(Patch, Patch)> {
+        let undo_pages: Vec<(usize, Page)> = self
+            .pages
+            .into_iter()
+            .filter(|(index, page)| after.pages.get(*index) != Some(page))
+            .collect();
+        // Pages the step changed, plus any it added past the old end
+        let redo_pages = undo_pages
+            .iter()
+            .map(|(index, _)| *index)
+            .chain(self.page_count..after.pages.len())
+            .filter_map(|index| Some((index, after.pages.get(index)?.clone())))
+            .collect();
+        let theme = self.theme.filter(|theme| *theme != after.theme);
+        let assets = self.assets.filter(|assets| *assets != after.assets);
+
+        let unchanged = undo_pages.is_empty()
+            && self.page_count == after.pages.len()
+            && self.active_page == after.active_page
+            && self.next_id == after.next_id
+            && theme.is_none()
+            && assets.is_none();
+        if unchanged {
+            return None;
+        }
+        let redo = Patch {
+            pages: redo_pages,
+            page_count: after.pages.len(),
+            active_page: after.active_page.clone(),
+            next_id: after.next_id,
+            theme: theme.is_some().then(|| after.theme.clone()),
+            assets: assets.is_some().then(|| after.assets.clone()),
+        };
+        let undo = Patch {
+            pages: undo_pages,
+            page_count: self.page_count,
+            active_page: self.active_page,
+            next_id: self.next_id,
+            theme,
+            assets,
+        };
+        Some((undo, redo))
+    }
+
+    /// One patch with the effect of applying `self` and then `next`. Only used for steps that
+    /// keep the page list as it is.
+    fn then(mut self, next: Patch) -> Patch {
+        for (index, page) in next.pages {
+            match self.pages.iter_mut().find(|(existing, _)| *existing == index) {
+                Some(existing) => existing.1 = page,
+                None => self.pages.push((index, page)),
+            }
+        }
+        self.pages.sort_by_key(|(index, _)| *index);
+        Patch {
+            pages: self.pages,
+            page_count: next.page_count,
+            active_page: next.active_page,
+            next_id: next.next_id,
+            theme: next.theme.or(self.theme),
+            assets: next.assets.or(self.assets),
+        }
+    }
+
+    fn apply(&self, document: &mut Document) {
+        document.pages.truncate(self.page_count);
+        for (index, page) in &self.pages {
+            match document.pages.get_mut(*index) {
+                Some(existing) => *existing = page.clone(),
+                None => document.pages.push(page.clone()),
+            }
+        }
+        document.active_page.clone_from(&self.active_page);
+        document.next_id = self.next_id;
+        if let Some(theme) = &self.theme {
+            document.theme = theme.clone();
+        }
+        if let Some(assets) = &self.assets {
+            document.assets = assets.clone();
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct HistoryEntry {
+    command: Command,
+    /// Turns the document after the step back into the one before it.
+    undo: Patch,
+    /// Turns the document before the step into the one after it.
+    redo: Patch,
+}
+
+/// Bounded undo/redo stacks of document patches, one entry per recorded [`Command`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    /// Cleared by undo/redo so the next edit starts a fresh step.
+    can_coalesce: bool,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `command`, which turned the document captured in `before` into `after`. Any
+    /// redo steps are discarded.
+    pub fn record(&mut self, command: Command, before: Patch, after: &Document) {
+        let Some((undo, redo)) = before.diff(after) else {
+            return;
+        };
+        self.redo_stack.clear();
+
+        if let Some(last) = self.undo_stack.back_mut().filter(|_| self.can_coalesce) {
+            if last.command.absorbs(&command) {
+                if matches!(last.command, Command::AddComponent { .. }) {
+                    last.command = command;
+                }
+                // One step from the document before `last` straight to `after`
+                last.undo = undo.then(last.undo.clone());
+                last.redo = last.redo.clone().then(redo);
+                return;
+            }
+        }
+
+        self.can_coalesce = true;
+        self.undo_stack.push_back(HistoryEntry { command, undo, redo });
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Steps `document` back once. Returns whether there was a step to undo.
+    pub fn undo(&mut self, document: &mut Document) -> bool {
+        let Some(entry) = self.undo_stack.pop_back() else {
+            return false;
+        };
+        entry.undo.apply(document);
+        self.redo_stack.push(entry);
+        self.can_coalesce = false;
+        true
+    }
+
+    /// Re-applies the most recently undone step to `document`. Returns whether there was one.
+    pub fn redo(&mut self, document: &mut Document) -> bool {
+        let Some(entry) = self.redo_stack.pop() else {
+            return false;
+        };
+        entry.redo.apply(document);
+        self.undo_stack.push_back(entry);
+        self.can_coalesce = false;
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::ComponentType;
+
+    /// Applies `edit` to `document` and records it as `command`.
+    fn edit(history: &mut History, document: &mut Document, command: Command, change: impl FnOnce(&mut Document)) {
+        let before = Patch::capture(document, &command);
+        change(document);
+        history.record(command, before, document);
+    }
+
+    fn content(component_id: &str) -> Command {
+        Command::UpdateComponentContent { component_id: component_id.into() }
+    }
+
+    #[test]
+    fn steps_are_undone_and_redone() {
+        let mut history = History::new();
+        let mut document = Document::new();
+        let start = document.clone();
+        let id = format!("component_{}", document.next_id);
+        edit(&mut history, &mut document, Command::AddComponent { component_id: id.clone() }, |document| {
+            document.add_component(ComponentType::Text);
+        });
+        edit(&mut history, &mut document, Command::MoveToDropZone { component_id: id.clone() }, |document| {
+            document.move_to_drop_zone(&id, (0.0, 0.0));
+        });
+        let placed = document.clone();
+        edit(&mut history, &mut document, Command::AddPage { page_id: "page_x".into() }, |document| {
+            document.add_page("About");
+        });
+        let with_page = document.clone();
+
+        assert!(history.undo(&mut document));
+        assert_eq!(document, placed);
+        // Creating and placing the component was one step
+        assert!(history.undo(&mut document));
+        assert_eq!(document, start);
+        assert!(!history.undo(&mut document));
+
+        assert!(history.redo(&mut document));
+        assert_eq!(document, placed);
+        assert!(history.redo(&mut document));
+        assert_eq!(document, with_page);
+        assert!(!history.can_redo());
+
+        // A new edit after undoing drops the redo steps
+        history.undo(&mut document);
+        edit(&mut history, &mut document, content(&id), |document| document.update_component_content(&id, "Hi"));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn text_edits_to_one_component_are_one_step() {
+        let mut history = History::new();
+        let mut document = Document::new();
+        let id = document.add_component(ComponentType::Text);
+        document.move_to_drop_zone(&id, (0.0, 0.0));
+        let start = document.clone();
+        for text in ["H", "He", "Hey"] {
+            edit(&mut history, &mut document, content(&id), |document| document.update_component_content(&id, text));
+        }
+        assert!(history.undo(&mut document));
+        assert_eq!(document, start);
+        assert!(!history.can_undo());
+
+        // Undoing ends the run, so the next edit is a step of its own
+        history.redo(&mut document);
+        edit(&mut history, &mut document, content(&id), |document| document.update_component_content(&id, "Hey!"));
+        history.undo(&mut document);
+        assert_eq!(document.get_component(&id).unwrap().properties.content.as_deref(), Some("Hey"));
+    }
+
+    #[test]
+    fn palette_components_are_placed_in_the_same_step() {
+        let mut history = History::new();
+        let mut document = Document::new();
+        let grid = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&grid, (0.0, 0.0));
+        let start = document.clone();
+        let id = format!("component_{}", document.next_id);
+        edit(&mut history, &mut document, Command::AddComponent { component_id: id.clone() }, |document| {
+            document.add_component(ComponentType::Button);
+        });
+        let command = Command::InsertIntoColumn { component_id: id.clone(), column_id: grid.clone() };
+        edit(&mut history, &mut document, command, |document| document.insert_into_column(&id, &grid, 0, 0));
+        // A later move of the same component is its own step
+        let command = Command::InsertIntoColumn { component_id: id.clone(), column_id: grid.clone() };
+        edit(&mut history, &mut document, command, |document| document.insert_into_column(&id, &grid, 1, 0));
+
+        history.undo(&mut document);
+        assert_eq!(document.position_in_column(&id), Some((grid.clone(), 0, 0)));
+        history.undo(&mut document);
+        assert_eq!(document, start);
+    }
+
+    #[test]
+    fn only_the_newest_steps_are_kept() {
+        let mut history = History::new();
+        let mut document = Document::new();
+        for index in 0..HISTORY_LIMIT + 5 {
+            let command = Command::AddPage { page_id: format!("page_{index}") };
+            edit(&mut history, &mut document, command, |document| {
+                document.add_page("Page");
+            });
+        }
+        let mut undone = 0;
+        while history.undo(&mut document) {
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(document.pages.len(), 6);
+    }
+
+    #[test]
+    fn steps_leave_untouched_parts_out() {
+        let mut history = History::new();
+        let mut document = Document::new();
+        document.add_page("About");
+        let id = document.add_component(ComponentType::Text);
+        let before = Patch::capture(&document, &content(&id));
+        assert_eq!(before.pages.len(), 1);
+        assert_eq!((before.theme, before.assets), (None, None));
+
+        edit(&mut history, &mut document, content(&id), |document| document.update_component_content(&id, "Hi"));
+        let entry = history.undo_stack.back().unwrap();
+        assert_eq!(entry.undo.pages.len(), 1);
+        assert_eq!(entry.undo.assets, None);
+        assert_eq!(entry.undo.theme, None);
+    }
+}
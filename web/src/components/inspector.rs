@@ -0,0 +1,382 @@
+use dioxus::prelude::*;
+use crate::components::{use_drag_drop_context, Breakpoint, ComponentType, DraggableComponent, GridSettings, GAPS, MAX_COLUMNS};
+
+/// Right-hand panel that edits the selected component's name, content, Tailwind classes and
+/// attributes. Renders nothing while no component is selected.
+#[component]
+pub fn PropertiesInspector() -> Element {
+    let drag_context = use_drag_drop_context();
+    let mut new_classes = use_signal(String::new);
+    let mut new_attribute_key = use_signal(String::new);
+    let mut new_attribute_value = use_signal(String::new);
+
+    let Some(component) = drag_context.selected_component() else {
+        return rsx! {};
+    };
+    let component_id = component.id.clone();
+    // Class edits go to the breakpoint picked here, and apply at every size until one is
+    let scope = drag_context.style_breakpoint();
+    let scoped_styles: Vec<(String, String)> = component
+        .properties
+        .styles
+        .iter()
+        .filter_map(|style| match Breakpoint::split(style) {
+            (breakpoint, class) if breakpoint == scope => Some((style.clone(), class.to_string())),
+            _ => None,
+        })
+        .collect();
+    let scope_count = |breakpoint: Option<Breakpoint>| {
+        component.properties.styles.iter().filter(|style| Breakpoint::split(style).0 == breakpoint).count()
+    };
+
+    let mut add_classes = {
+        let component_id = component_id.clone();
+        move || {
+            if !new_classes().trim().is_empty() {
+                drag_context.add_scoped_styles(&component_id, &new_classes());
+                new_classes.set(String::new());
+            }
+        }
+    };
+    let mut add_attribute = {
+        let component_id = component_id.clone();
+        move || {
+            let key = new_attribute_key().trim().to_string();
+            if !key.is_empty() {
+                drag_context.set_component_attribute(&component_id, &key, &new_attribute_value());
+                new_attribute_key.set(String::new());
+                new_attribute_value.set(String::new());
+            }
+        }
+    };
+
+    rsx! {
+        aside { class: "w-80 shrink-0 bg-white border border-gray-200 rounded-lg shadow-sm p-4 space-y-6 sticky top-20",
+            // Header
+            div { class: "flex justify-between items-center pb-2 border-b border-gray-100",
+                div {
+                    h2 { class: "text-lg font-semibold", "Properties" }
+                    span { class: "text-xs text-gray-400", "{component.component_type:?} · {component.id}" }
+                }
+                button {
+                    class: "p-1 rounded hover:bg-gray-100 text-gray-500",
+                    onclick: move |_| drag_context.select_component(None),
+                    "×"
+                }
+            }
+            if component.locked {
+                p { class: "text-sm text-amber-700 bg-amber-50 rounded p-2",
+                    "This component is locked. Unlock it in the Layers panel to edit it."
+                }
+            }
+            fieldset { class: "space-y-6 disabled:opacity-60", disabled: component.locked,
+                // Name
+                div {
+                    label { class: "block mb-1 text-sm font-medium text-gray-900", "Name" }
+                    input {
+                        class: "w-full p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
+                        value: "{component.name}",
+                        oninput: {
+                            let component_id = component_id.clone();
+                            move |e: FormEvent| drag_context.update_component_name(&component_id, &e.value())
+                        },
+                    }
+                }
+                // Content
+                if let Some(placeholder) = component.component_type.default_body() {
+                    div {
+                        label { class: "block mb-1 text-sm font-medium text-gray-900", "Content" }
+                        textarea {
+                            class: "w-full p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
+                            rows: "3",
+                            placeholder: "{placeholder}",
+                            value: "{component.properties.content.clone().unwrap_or_default()}",
+                            oninput: {
+                                let component_id = component_id.clone();
+                                move |e: FormEvent| drag_context.update_component_content(&component_id, &e.value())
+                            },
+                        }
+                    }
+                }
+                // Image
+                if component.component_type == ComponentType::Image {
+                    ImageEditor { component: component.clone() }
+                }
+                // Grid
+                if component.component_type.is_layout() {
+                    GridEditor { component: component.clone() }
+                }
+                // Tailwind classes
+                div {
+                    label { class: "block mb-1 text-sm font-medium text-gray-900", "Tailwind Classes" }
+                    div { class: "flex rounded-lg border border-gray-300 overflow-hidden text-xs mb-1",
+                        for breakpoint in std::iter::once(None).chain(Breakpoint::ALL.map(Some)) {
+                            button {
+                                class: "flex-1 px-2 py-1",
+                                class: if breakpoint == scope { "bg-gray-800 text-white" } else { "bg-white text-gray-600 hover:bg-gray-100" },
+                                r#type: "button",
+                                onclick: move |_| drag_context.set_style_breakpoint(breakpoint),
+                                match breakpoint {
+                                    Some(breakpoint) => breakpoint.prefix(),
+                                    None => "All sizes",
+                                }
+                                if scope_count(breakpoint) > 0 {
+                                    span { class: "ml-1 opacity-60", "{scope_count(breakpoint)}" }
+                                }
+                            }
+                        }
+                    }
+                    p { class: "text-xs text-gray-500 mb-2",
+                        match scope {
+                            Some(breakpoint) => format!("Applies from {}px wide up, as {}: classes", breakpoint.min_width(), breakpoint.prefix()),
+                            None => "Applies at every screen size".to_string(),
+                        }
+                    }
+                    div { class: "flex flex-wrap gap-1 mb-2",
+                        if scoped_styles.is_empty() {
+                            span { class: "text-xs text-gray-400", "No custom classes" }
+                        }
+                        for (style, class) in scoped_styles {
+                            span {
+                                key: "{style}",
+                                class: "inline-flex items-center gap-1 px-2 py-0.5 bg-blue-50 text-blue-700 rounded font-mono text-xs",
+                                "{class}"
+                                button {
+                                    class: "hover:text-red-600",
+                                    onclick: {
+                                        let component_id = component_id.clone();
+                                        move |_| drag_context.remove_component_style(&component_id, &style)
+                                    },
+                                    "×"
+                                }
+                            }
+                        }
+                    }
+                    div { class: "flex space-x-2",
+                        input {
+                            class: "flex-1 min-w-0 p-2 border border-gray-300 rounded-lg font-mono text-sm focus:ring-blue-500 focus:border-blue-500",
+                            placeholder: "e.g. rounded-lg shadow-md",
+                            value: "{new_classes}",
+                            oninput: move |e| new_classes.set(e.value()),
+                            onkeydown: {
+                                let mut add_classes = add_classes.clone();
+                                move |e: KeyboardEvent| {
+                                    if e.key() == Key::Enter {
+                                        add_classes();
+                                    }
+                                }
+                            },
+                        }
+                        button {
+                            class: "px-3 py-2 bg-blue-600 text-white rounded hover:bg-blue-700 text-sm",
+                            onclick: move |_| add_classes(),
+                            "Add"
+                        }
+                    }
+                }
+                // Attributes
+                div {
+                    label { class: "block mb-1 text-sm font-medium text-gray-900", "Attributes" }
+                    div { class: "space-y-2 mb-2",
+                        for (key, value) in component.sorted_attributes().into_iter().map(|(k, v)| (k.clone(), v.clone())) {
+                            div { key: "{key}", class: "flex items-center space-x-2",
+                                span { class: "w-24 truncate font-mono text-xs text-gray-600", title: "{key}", "{key}" }
+                                input {
+                                    class: "flex-1 min-w-0 p-1 border border-gray-300 rounded text-sm",
+                                    value: "{value}",
+                                    oninput: {
+                                        let component_id = component_id.clone();
+                                        let key = key.clone();
+                                        move |e: FormEvent| drag_context.set_component_attribute(&component_id, &key, &e.value())
+                                    },
+                                }
+                                button {
+                                    class: "text-red-500 hover:text-red-700 text-xs",
+                                    onclick: {
+                                        let component_id = component_id.clone();
+                                        move |_| drag_context.remove_component_attribute(&component_id, &key)
+                                    },
+                                    "×"
+                                }
+                            }
+                        }
+                    }
+                    div { class: "flex space-x-2",
+                        input {
+                            class: "w-24 p-1 border border-gray-300 rounded font-mono text-sm",
+                            placeholder: "name",
+                            value: "{new_attribute_key}",
+                            oninput: move |e| new_attribute_key.set(e.value()),
+                        }
+                        input {
+                            class: "flex-1 min-w-0 p-1 border border-gray-300 rounded text-sm",
+                            placeholder: "value",
+                            value: "{new_attribute_value}",
+                            oninput: move |e| new_attribute_value.set(e.value()),
+                            onkeydown: {
+                                let mut add_attribute = add_attribute.clone();
+                                move |e: KeyboardEvent| {
+                                    if e.key() == Key::Enter {
+                                        add_attribute();
+                                    }
+                                }
+                            },
+                        }
+                        button {
+                            class: "px-3 py-1 bg-blue-600 text-white rounded hover:bg-blue-700 text-sm",
+                            onclick: move |_| add_attribute(),
+                            "Add"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Picks the library image an Image component shows.
+#[component]
+fn ImageEditor(component: DraggableComponent) -> Element {
+    let drag_context = use_drag_drop_context();
+    let images: Vec<_> = drag_context.assets().into_iter().filter(|asset| asset.is_image()).collect();
+    let current = component.properties.asset_id.clone().unwrap_or_default();
+
+    rsx! {
+        div {
+            label { class: "block mb-1 text-sm font-medium text-gray-900", "Image" }
+            select {
+                class: "w-full p-2 border border-gray-300 rounded-lg text-sm",
+                onchange: move |e: FormEvent| {
+                    let asset_id = e.value();
+                    drag_context.set_component_asset(&component.id, Some(asset_id.as_str()).filter(|id| !id.is_empty()));
+                },
+                option { value: "", selected: current.is_empty(), "Placeholder" }
+                for asset in images {
+                    option { value: "{asset.id}", selected: asset.id == current, "{asset.name}" }
+                }
+            }
+            p { class: "mt-1 text-xs text-gray-500", "Or drag an image from the Assets panel onto it." }
+        }
+    }
+}
+
+/// Column count, gap, tracks per breakpoint and column spans of a grid layout.
+#[component]
+fn GridEditor(component: DraggableComponent) -> Element {
+    let drag_context = use_drag_drop_context();
+    let grid = component.grid.clone().unwrap_or_default();
+    let column_count = component.columns.len();
+    let select_class = "p-1 border border-gray-300 rounded text-sm";
+
+    rsx! {
+        div { class: "space-y-3",
+            label { class: "block text-sm font-medium text-gray-900", "Grid" }
+            div { class: "grid grid-cols-2 gap-2 text-sm",
+                label { class: "flex flex-col gap-1 text-gray-600",
+                    "Columns"
+                    select {
+                        class: select_class,
+                        onchange: {
+                            let component_id = component.id.clone();
+                            move |e: FormEvent| {
+                                if let Ok(count) = e.value().parse() {
+                                    drag_context.set_grid_column_count(&component_id, count);
+                                }
+                            }
+                        },
+                        for count in 1..=MAX_COLUMNS {
+                            option { value: "{count}", selected: count == column_count, "{count}" }
+                        }
+                    }
+                }
+                label { class: "flex flex-col gap-1 text-gray-600",
+                    "Gap"
+                    select {
+                        class: select_class,
+                        onchange: {
+                            let component_id = component.id.clone();
+                            let grid = grid.clone();
+                            move |e: FormEvent| {
+                                if let Ok(gap) = e.value().parse() {
+                                    drag_context.set_grid_settings(&component_id, GridSettings { gap, ..grid.clone() });
+                                }
+                            }
+                        },
+                        for gap in GAPS {
+                            option { value: "{gap}", selected: gap == grid.gap, "gap-{gap}" }
+                        }
+                    }
+                }
+            }
+            // Tracks: the smallest screens first, then each breakpoint from its width up
+            div { class: "grid grid-cols-4 gap-2 text-sm",
+                label { class: "flex flex-col gap-1 text-gray-600",
+                    "Base"
+                    select {
+                        class: select_class,
+                        onchange: {
+                            let component_id = component.id.clone();
+                            let grid = grid.clone();
+                            move |e: FormEvent| {
+                                let columns = e.value().parse().ok();
+                                drag_context.set_grid_settings(&component_id, GridSettings { columns, ..grid.clone() });
+                            }
+                        },
+                        option { value: "", selected: grid.columns.is_none(), "Auto" }
+                        for count in 1..=MAX_COLUMNS {
+                            option { value: "{count}", selected: grid.columns == Some(count), "{count}" }
+                        }
+                    }
+                }
+                for breakpoint in Breakpoint::ALL {
+                    label { class: "flex flex-col gap-1 text-gray-600",
+                        "{breakpoint.prefix()}"
+                        select {
+                            class: select_class,
+                            onchange: {
+                                let component_id = component.id.clone();
+                                let mut grid = grid.clone();
+                                move |e: FormEvent| {
+                                    match e.value().parse() {
+                                        Ok(count) => grid.breakpoint_columns.insert(breakpoint, count),
+                                        Err(_) => grid.breakpoint_columns.remove(&breakpoint),
+                                    };
+                                    drag_context.set_grid_settings(&component_id, grid.clone());
+                                }
+                            },
+                            option { value: "", selected: !grid.breakpoint_columns.contains_key(&breakpoint), "–" }
+                            for count in 1..=MAX_COLUMNS {
+                                option { value: "{count}", selected: grid.breakpoint_columns.get(&breakpoint) == Some(&count), "{count}" }
+                            }
+                        }
+                    }
+                }
+            }
+            p { class: "text-xs text-gray-500",
+                "Tracks per screen size. Auto gives each column its own track; – keeps the smaller size's count."
+            }
+            // Spans
+            div { class: "space-y-1 text-sm",
+                for (index, slot) in component.columns.iter().enumerate() {
+                    label { key: "{index}", class: "flex items-center justify-between text-gray-600",
+                        "Column {index + 1} span"
+                        select {
+                            class: select_class,
+                            onchange: {
+                                let component_id = component.id.clone();
+                                move |e: FormEvent| {
+                                    if let Ok(span) = e.value().parse() {
+                                        drag_context.set_column_span(&component_id, index, span);
+                                    }
+                                }
+                            },
+                            for span in 1..=MAX_COLUMNS {
+                                option { value: "{span}", selected: span == slot.span, "{span}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,30 +1,110 @@
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
+use dioxus::prelude::server_fn::codec::Json;
+use dioxus::html::{FileEngine, HasFileData};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::components::{format_size, read_uploads, use_drag_drop_context, Asset, ALLOWED_TYPES};
 
-#[derive(Clone, PartialEq)]
-struct Asset {
-    id: String,
-    name: String,
-    file_type: String,
-    preview_url: String,
-    size: String,
+/// A file in the server's asset library, as listed by [`list_library`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryAsset {
+    /// SHA-256 of the contents, hex encoded. Uploading the same file again finds this entry.
+    pub hash: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+    /// Width and height in pixels, for images the server could measure.
+    pub dimensions: Option<(u32, u32)>,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+impl LibraryAsset {
+    /// Name of the stored file: the hash plus an extension for the type.
+    pub fn file_name(&self) -> String {
+        let extension = match self.mime_type.as_str() {
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            "image/svg+xml" => "svg",
+            "video/mp4" => "mp4",
+            _ => "bin",
+        };
+        format!("{}.{extension}", self.hash)
+    }
+
+    /// Where the server serves the file. The contents decide it, so it never changes.
+    pub fn url(&self) -> String {
+        format!("/library/{}", self.file_name())
+    }
+
+    /// Id of the document asset for the library file with contents `hash`.
+    pub fn asset_id(hash: &str) -> String {
+        format!("asset_{hash}")
+    }
+
+    /// The entry as a document asset, previewed from the library.
+    pub fn to_asset(&self) -> Asset {
+        Asset {
+            id: Self::asset_id(&self.hash),
+            name: self.name.clone(),
+            file_type: self.mime_type.clone(),
+            preview_url: self.url(),
+            size: format_size(self.size),
+        }
+    }
 }
 
 #[component]
 pub fn AssetPanel() -> Element {
-    let mut assets = use_signal(|| Vec::<Asset>::new());
+    let drag_context = use_drag_drop_context();
+    let mut library = use_resource(list_library);
     let mut is_dragover = use_signal(|| false);
+    // Why files from the last upload were turned away
+    let mut errors = use_signal(Vec::<String>::new);
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| None::<String>);
+
+    // The library first, then files only this project has (uploaded while the server was away)
+    let library_assets = match &*library.read() {
+        Some(Ok(assets)) => assets.clone(),
+        _ => Vec::new(),
+    };
+    let mut entries: Vec<(Asset, Option<LibraryAsset>)> =
+        library_assets.into_iter().map(|entry| (entry.to_asset(), Some(entry))).collect();
+    for asset in drag_context.assets() {
+        if !entries.iter().any(|(listed, _)| listed.id == asset.id) {
+            entries.push((asset, None));
+        }
+    }
+    let selected_entry = entries.iter().find(|(asset, _)| selected.read().as_ref() == Some(&asset.id)).cloned();
+    let search = query().trim().to_lowercase();
+    entries.retain(|(asset, _)| asset.name.to_lowercase().contains(&search));
 
-    // Handle file uploads - simplified for now
-    let mut on_file_change = move |_files: Vec<String>| {
-        let asset_count = assets.read().len();
-        // Placeholder implementation
-        assets.write().push(Asset {
-            id: format!("asset_{}", asset_count + 1),
-            name: "Sample Asset".to_string(),
-            file_type: "jpg".to_string(),
-            preview_url: "".to_string(),
-            size: "1.2 KB".to_string(),
-        });
+    // Files from the file picker or dropped from the OS
+    let upload = move |files: Arc<dyn FileEngine>| async move {
+        let mut rejected = Vec::new();
+        for upload in read_uploads(files).await {
+            let upload = match upload {
+                Ok(upload) => upload,
+                Err(err) => {
+                    rejected.push(err);
+                    continue;
+                }
+            };
+            let data = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&upload.bytes);
+            match upload_asset(upload.name.clone(), data).await {
+                Ok(stored) => drag_context.add_asset(stored.to_asset()),
+                Err(err) => {
+                    // Kept in the project itself, so the file isn't lost
+                    rejected.push(format!("{} is only kept in this project: {err}", upload.name));
+                    drag_context.add_asset(upload.to_asset());
+                }
+            }
+        }
+        errors.set(rejected);
+        library.restart();
     };
 
     // Handle drag and drop
@@ -38,29 +118,37 @@ pub fn AssetPanel() -> Element {
     };
 
     let ondrop = move |e: DragEvent| {
+        // Stops the browser from opening the file itself
         e.prevent_default();
         is_dragover.set(false);
-        // For now, just add a placeholder asset
-        on_file_change(vec!["dropped_file".to_string()]);
+        if let Some(files) = e.files() {
+            spawn(upload(files));
+        }
     };
 
-    // Trigger file input click - simplified
-    let trigger_file_input = move |_| {
-        // For now, just add a sample asset
-        on_file_change(vec!["clicked_file".to_string()]);
+    let on_file_change = move |e: FormEvent| async move {
+        if let Some(files) = e.files() {
+            upload(files).await;
+        }
     };
 
     rsx! {
         div { class: "flex flex-col h-full p-4",
             h2 { class: "text-xl font-semibold mb-4", "Assets" }
             // Upload Area
-            div {
-                class: "border-2 border-dashed rounded-lg p-6 mb-4 text-center cursor-pointer transition-colors",
+            label {
+                class: "block border-2 border-dashed rounded-lg p-6 mb-4 text-center cursor-pointer transition-colors",
                 class: if is_dragover() { "border-blue-500 bg-blue-50" } else { "border-gray-300 hover:border-blue-400" },
                 ondragover,
                 ondragleave,
                 ondrop,
-                onclick: trigger_file_input,
+                input {
+                    r#type: "file",
+                    class: "hidden",
+                    multiple: true,
+                    accept: ALLOWED_TYPES.join(","),
+                    onchange: on_file_change,
+                }
                 svg {
                     class: "mx-auto h-12 w-12 text-gray-400",
                     fill: "none",
@@ -78,64 +166,135 @@ pub fn AssetPanel() -> Element {
                     " or drag and drop"
                 }
                 p { class: "text-xs text-gray-500", "PNG, JPG, GIF, SVG, MP4 up to 10MB" }
-                        // Note: File input temporarily simplified for compilation
+            }
+            if !errors.read().is_empty() {
+                ul { class: "mb-4 space-y-1 text-sm text-red-600",
+                    for error in errors() {
+                        li { "{error}" }
+                    }
+                }
+            }
+            // Search
+            input {
+                class: "w-full p-2 mb-4 border border-gray-300 rounded-lg text-sm focus:ring-blue-500 focus:border-blue-500",
+                r#type: "search",
+                placeholder: "Search assets...",
+                value: "{query}",
+                oninput: move |e| query.set(e.value()),
             }
             // Asset Grid
             div { class: "flex-1 overflow-y-auto",
-                if assets.read().is_empty() {
+                if let Some(Err(err)) = &*library.read() {
+                    p { class: "mb-4 text-sm text-red-600", "Could not load the asset library: {err}" }
+                }
+                if entries.is_empty() && search.is_empty() {
                     div { class: "text-center py-8 text-gray-500",
                         "No assets yet. Upload some files to get started."
                     }
+                } else if entries.is_empty() {
+                    div { class: "text-center py-8 text-gray-500", "No assets match \"{query}\"." }
                 } else {
                     div { class: "grid grid-cols-2 sm:grid-cols-3 gap-4",
-                        {
-                            assets()
-                                .into_iter()
-                                .map(|asset| {
-                                    rsx! {
-                                        AssetCard {
-                                            key: "{asset.id}",
-                                            asset: asset.clone(),
-                                            on_drag_start: move || {
-                                                log::info!("Dragging asset: {}", asset.name);
-                                            },
-                                        }
-                                    }
-                                })
+                        for (asset, _) in entries {
+                            AssetCard {
+                                key: "{asset.id}",
+                                is_selected: selected.read().as_ref() == Some(&asset.id),
+                                on_select: {
+                                    let asset_id = asset.id.clone();
+                                    move |_| selected.set(Some(asset_id.clone()))
+                                },
+                                asset,
+                            }
                         }
                     }
                 }
             }
-            // Selected Asset Info (would show when an asset is selected)
+            // Selected Asset Info
             div { class: "border-t border-gray-200 pt-4 mt-4",
                 h3 { class: "font-medium mb-2", "Asset Details" }
-                p { class: "text-sm text-gray-500", "Select an asset to view details" }
+                match selected_entry {
+                    Some((asset, entry)) => rsx! {
+                        AssetDetails {
+                            key: "{asset.id}",
+                            asset: asset.clone(),
+                            entry: entry.clone(),
+                            on_rename: {
+                                let asset_id = asset.id.clone();
+                                let hash = entry.as_ref().map(|entry| entry.hash.clone());
+                                move |name: String| {
+                                    let asset_id = asset_id.clone();
+                                    let hash = hash.clone();
+                                    async move {
+                                        if let Some(hash) = hash {
+                                            if let Err(err) = rename_library_asset(hash, name.clone()).await {
+                                                errors.set(vec![format!("Could not rename {name}: {err}")]);
+                                                return;
+                                            }
+                                            library.restart();
+                                        }
+                                        drag_context.rename_asset(&asset_id, &name);
+                                    }
+                                }
+                            },
+                            on_delete: {
+                                let asset_id = asset.id.clone();
+                                let hash = entry.as_ref().map(|entry| entry.hash.clone());
+                                move |_| {
+                                    let asset_id = asset_id.clone();
+                                    let hash = hash.clone();
+                                    async move {
+                                        if let Some(hash) = hash {
+                                            if let Err(err) = delete_library_asset(hash).await {
+                                                errors.set(vec![format!("Could not delete the asset: {err}")]);
+                                                return;
+                                            }
+                                            library.restart();
+                                        }
+                                        drag_context.remove_asset(&asset_id);
+                                        selected.set(None);
+                                    }
+                                }
+                            },
+                        }
+                    },
+                    None => rsx! {
+                        p { class: "text-sm text-gray-500", "Select an asset to view details" }
+                    },
+                }
             }
         }
     }
 }
 
 #[component]
-fn AssetCard(asset: Asset, on_drag_start: EventHandler) -> Element {
+fn AssetCard(asset: Asset, is_selected: bool, on_select: EventHandler) -> Element {
+    let drag_context = use_drag_drop_context();
+    let asset_for_drag = asset.clone();
+    let title = if asset.is_image() { "Drag onto an Image or into a column" } else { "" };
+
     rsx! {
         div {
-            class: "border rounded-lg overflow-hidden hover:shadow-md transition-shadow bg-white",
+            class: "border rounded-lg overflow-hidden hover:shadow-md transition-shadow bg-white cursor-move",
+            class: if is_selected { "ring-2 ring-blue-500" },
             draggable: "true",
-            ondragstart: move |_| on_drag_start.call(()),
+            title,
+            onclick: move |_| on_select.call(()),
+            ondragstart: move |_| {
+                // Library files join the project once they are used in it
+                if drag_context.get_asset(&asset_for_drag.id).is_none() {
+                    drag_context.add_asset(asset_for_drag.clone());
+                }
+                drag_context.set_dragged_asset(Some(asset_for_drag.id.clone()));
+            },
+            ondragend: move |_| drag_context.set_dragged_asset(None),
             // Preview
             div { class: "aspect-square bg-gray-100 flex items-center justify-center",
-                if asset.file_type == "jpg" || asset.file_type == "jpeg" || asset.file_type == "png"
-                    || asset.file_type == "gif"
-                {
+                if asset.is_image() {
                     img {
-                        class: "object-cover w-full h-full",
+                        class: if asset.file_type == "image/svg+xml" { "object-contain w-full h-full p-4" } else { "object-cover w-full h-full" },
                         src: "{asset.preview_url}",
                         alt: "{asset.name}",
                     }
-                } else if asset.file_type == "svg" {
-                    div { class: "p-4",
-                        svg { class: "w-full h-full", view_box: "0 0 24 24" }
-                    }
                 } else {
                     div { class: "text-center p-4",
                         svg {
@@ -162,8 +321,262 @@ fn AssetCard(asset: Asset, on_drag_start: EventHandler) -> Element {
                     p { class: "text-xs font-medium text-gray-900 truncate", "{asset.name}" }
                     p { class: "text-xs text-gray-500", "{asset.size}" }
                 }
-                p { class: "text-xs text-gray-500 uppercase mt-1", "{asset.file_type}" }
+                p { class: "text-xs text-gray-500 uppercase mt-1", "{asset.type_label()}" }
             }
         }
     }
-}
\ No newline at end of file
+}
+/// Name, metadata and pages using the selected asset, with rename and a two-step delete.
+#[component]
+fn AssetDetails(asset: Asset, entry: Option<LibraryAsset>, on_rename: EventHandler<String>, on_delete: EventHandler) -> Element {
+    let drag_context = use_drag_drop_context();
+    let mut name = use_signal(|| asset.name.clone());
+    let mut confirming_delete = use_signal(|| false);
+    let pages = drag_context.pages_using_asset(&asset.id);
+    let uploaded_at = entry.as_ref().map(|entry| entry.uploaded_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+    // Older projects previewed project-only files from `blob:` URLs, which die with the page
+    let stored = if asset.preview_url.starts_with("blob:") {
+        "Missing; upload it again"
+    } else {
+        "In this project only"
+    };
+    let used_on = if pages.is_empty() { "Not used on any page".to_string() } else { pages.join(", ") };
+
+    rsx! {
+        div { class: "space-y-3 text-sm",
+            input {
+                class: "w-full p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
+                value: "{name}",
+                oninput: move |e| name.set(e.value()),
+                onchange: move |_| {
+                    let new_name = name().trim().to_string();
+                    if !new_name.is_empty() && new_name != asset.name {
+                        on_rename.call(new_name);
+                    }
+                },
+            }
+            dl { class: "grid grid-cols-3 gap-x-2 gap-y-1 text-xs",
+                dt { class: "text-gray-500", "Type" }
+                dd { class: "col-span-2", "{asset.file_type}" }
+                dt { class: "text-gray-500", "Size" }
+                dd { class: "col-span-2", "{asset.size}" }
+                if let Some((width, height)) = entry.as_ref().and_then(|entry| entry.dimensions) {
+                    dt { class: "text-gray-500", "Dimensions" }
+                    dd { class: "col-span-2", "{width} × {height}" }
+                }
+                if let Some(entry) = &entry {
+                    dt { class: "text-gray-500", "Uploaded" }
+                    dd { class: "col-span-2", "{uploaded_at.clone().unwrap_or_default()}" }
+                    dt { class: "text-gray-500", "URL" }
+                    dd { class: "col-span-2 font-mono truncate", title: entry.url(), "{entry.url()}" }
+                } else {
+                    dt { class: "text-gray-500", "Stored" }
+                    dd { class: "col-span-2", "{stored}" }
+                }
+                dt { class: "text-gray-500", "Used on" }
+                dd { class: "col-span-2", "{used_on}" }
+            }
+            if confirming_delete() {
+                div { class: "p-2 rounded bg-red-50 space-y-2",
+                    p { class: "text-xs text-red-700",
+                        if pages.is_empty() {
+                            "Delete {asset.name}? No page uses it."
+                        } else {
+                            "Delete {asset.name}? Images on {used_on} go back to placeholders."
+                        }
+                    }
+                    div { class: "flex space-x-2 text-xs",
+                        button {
+                            class: "text-red-600 hover:text-red-800 font-medium",
+                            onclick: move |_| {
+                                confirming_delete.set(false);
+                                on_delete.call(());
+                            },
+                            "Delete"
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-gray-700",
+                            onclick: move |_| confirming_delete.set(false),
+                            "Cancel"
+                        }
+                    }
+                }
+            } else {
+                button {
+                    class: "text-xs text-red-500 hover:text-red-700",
+                    onclick: move |_| confirming_delete.set(true),
+                    "Delete asset"
+                }
+            }
+        }
+    }
+}
+
+#[server]
+async fn list_library() -> Result<Vec<LibraryAsset>, ServerFnError> {
+    let mut assets = store::read_all(&store::library_dir())?;
+    assets.sort_by_key(|asset| std::cmp::Reverse(asset.uploaded_at));
+    Ok(assets)
+}
+
+/// Stores a file sent as unpadded URL-safe base64. A file already in the library comes back
+/// as it was stored.
+// JSON, like `save_project`, rather than a URL-encoded form of the whole file
+#[server(input = Json)]
+async fn upload_asset(name: String, data: String) -> Result<LibraryAsset, ServerFnError> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|err| ServerFnError::new(format!("{name} arrived damaged: {err}")))?;
+    store::write(&store::library_dir(), &name, &bytes)
+}
+
+#[server]
+async fn rename_library_asset(hash: String, name: String) -> Result<LibraryAsset, ServerFnError> {
+    store::rename(&store::library_dir(), &hash, &name)
+}
+
+/// Deletes a file no saved project holds. The open project is checked by the panel, and may
+/// not be saved yet.
+#[server]
+async fn delete_library_asset(hash: String) -> Result<(), ServerFnError> {
+    use super::projects::store as projects;
+
+    let holders = projects::projects_using_asset(&projects::projects_dir(), &LibraryAsset::asset_id(&hash))?;
+    if !holders.is_empty() {
+        return Err(ServerFnError::new(format!("Still used by the saved projects {}", holders.join(", "))));
+    }
+    store::delete(&store::library_dir(), &hash)
+}
+
+/// Files in `DRAGNDROP_LIBRARY_DIR` (default `./library`), named after their hash, with their
+/// metadata in `index.json` next to them.
+#[cfg(any(feature = "server", test))]
+pub mod store {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, PoisonError};
+
+    use chrono::Utc;
+    use dioxus::prelude::ServerFnError;
+    use sha2::{Digest, Sha256};
+
+    use super::super::projects::store::write_atomic;
+    use super::LibraryAsset;
+    use crate::components::upload::{format_size, sniff_mime_type, MAX_UPLOAD_BYTES};
+
+    /// Held while the index is read and written back, so concurrent uploads can't lose entries.
+    static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+    #[cfg(feature = "server")]
+    pub fn library_dir() -> PathBuf {
+        std::env::var_os("DRAGNDROP_LIBRARY_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("library"))
+    }
+
+    fn index_path(dir: &Path) -> PathBuf {
+        dir.join("index.json")
+    }
+
+    pub fn read_all(dir: &Path) -> Result<Vec<LibraryAsset>, ServerFnError> {
+        match std::fs::read_to_string(index_path(dir)) {
+            Ok(json) => serde_json::from_str(&json).map_err(ServerFnError::new),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write_all(dir: &Path, assets: &[LibraryAsset]) -> Result<(), ServerFnError> {
+        let json = serde_json::to_string_pretty(assets).map_err(ServerFnError::new)?;
+        write_atomic(&index_path(dir), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Stores `bytes` under their hash, unless a file with the same contents is there already.
+    pub fn write(dir: &Path, name: &str, bytes: &[u8]) -> Result<LibraryAsset, ServerFnError> {
+        // The browser checked too, but the request could have come from anywhere
+        if bytes.len() as u64 > MAX_UPLOAD_BYTES {
+            return Err(ServerFnError::new(format!("{name} is larger than {}", format_size(MAX_UPLOAD_BYTES))));
+        }
+        let mime_type = sniff_mime_type(bytes)
+            .ok_or_else(|| ServerFnError::new(format!("{name} is not a PNG, JPG, GIF, SVG or MP4 file")))?;
+        let hash = format!("{:x}", Sha256::digest(bytes));
+
+        let _lock = INDEX_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut assets = read_all(dir)?;
+        if let Some(existing) = assets.iter().find(|asset| asset.hash == hash) {
+            return Ok(existing.clone());
+        }
+        let asset = LibraryAsset {
+            hash,
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            size: bytes.len() as u64,
+            dimensions: mime_type
+                .starts_with("image/")
+                .then(|| imagesize::blob_size(bytes).ok())
+                .flatten()
+                .map(|size| (size.width as u32, size.height as u32)),
+            uploaded_at: Utc::now(),
+        };
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(asset.file_name()), bytes)?;
+        assets.push(asset.clone());
+        write_all(dir, &assets)?;
+        Ok(asset)
+    }
+
+    pub fn rename(dir: &Path, hash: &str, name: &str) -> Result<LibraryAsset, ServerFnError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ServerFnError::new("Asset names can't be empty"));
+        }
+        let _lock = INDEX_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut assets = read_all(dir)?;
+        let asset = assets
+            .iter_mut()
+            .find(|asset| asset.hash == hash)
+            .ok_or_else(|| ServerFnError::new("No such asset in the library"))?;
+        asset.name = name.to_string();
+        let renamed = asset.clone();
+        write_all(dir, &assets)?;
+        Ok(renamed)
+    }
+
+    pub fn delete(dir: &Path, hash: &str) -> Result<(), ServerFnError> {
+        let _lock = INDEX_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut assets = read_all(dir)?;
+        let Some(index) = assets.iter().position(|asset| asset.hash == hash) else {
+            return Err(ServerFnError::new("No such asset in the library"));
+        };
+        let asset = assets.remove(index);
+        match std::fs::remove_file(dir.join(asset.file_name())) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+        write_all(dir, &assets)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn identical_files_are_stored_once() {
+            let dir = std::env::temp_dir().join(format!("dragndrop-library-{}", uuid::Uuid::new_v4().simple()));
+            let gif = b"GIF89a\x02\0\x03\0\0\0\0;";
+
+            let first = write(&dir, "logo.gif", gif).unwrap();
+            let again = write(&dir, "logo copy.gif", gif).unwrap();
+            assert_eq!(again, first);
+            assert_eq!(first.dimensions, Some((2, 3)));
+            assert_eq!(read_all(&dir).unwrap().len(), 1);
+            assert_eq!(std::fs::read(dir.join(first.file_name())).unwrap(), gif);
+            assert_eq!(rename(&dir, &first.hash, " Logo ").unwrap().name, "Logo");
+
+            delete(&dir, &first.hash).unwrap();
+            assert!(read_all(&dir).unwrap().is_empty());
+            assert!(!dir.join(first.file_name()).exists());
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
@@ -8,14 +8,10 @@ pub fn BlockItem(component_type: ComponentType) -> Element {
     let icon = component_type.icon();
     
     // Different styling for layout vs content components
-    let (border_color, hover_color, icon_color) = match component_type {
-        ComponentType::OneColumn | ComponentType::TwoColumns | ComponentType::ThreeColumns |
-        ComponentType::FourColumns | ComponentType::FiveColumns => {
-            ("border-blue-200", "hover:border-blue-400 hover:bg-blue-50", "text-blue-600")
-        },
-        _ => {
-            ("border-green-200", "hover:border-green-400 hover:bg-green-50", "text-green-600")
-        }
+    let (border_color, hover_color, icon_color) = if component_type.is_layout() {
+        ("border-blue-200", "hover:border-blue-400 hover:bg-blue-50", "text-blue-600")
+    } else {
+        ("border-green-200", "hover:border-green-400 hover:bg-green-50", "text-green-600")
     };
     
     rsx! {
@@ -23,10 +19,9 @@ pub fn BlockItem(component_type: ComponentType) -> Element {
             class: "bg-white p-3 rounded-lg border {border_color} {hover_color} cursor-move \
                    flex flex-col items-center text-center transition-colors",
             draggable: "true",
-            ondragstart: move |_e| {
-                let component_id = drag_context.add_component(component_type.clone());
-                drag_context.set_currently_dragging(Some(component_id));
-            },
+            // The component is created when it is dropped, so a cancelled drag leaves nothing behind
+            ondragstart: move |_e| drag_context.set_dragged_block(Some(component_type.clone())),
+            ondragend: move |_| drag_context.set_dragged_block(None),
             svg {
                 class: "w-6 h-6 mb-2 {icon_color}",
                 fill: "none",
@@ -51,13 +46,9 @@ pub fn BlocksPanel() -> Element {
             // Layout Components Section
             div {
                 h2 { class: "text-lg font-semibold mb-3 text-blue-600 border-b border-blue-200 pb-2", "📐 Layout Components" }
-                p { class: "text-sm text-gray-600 mb-4", "Drop these on the main canvas to create your page structure" }
+                p { class: "text-sm text-gray-600 mb-4", "Drop these on the main canvas to create your page structure, then set the columns in the Properties panel" }
                 div { class: "grid grid-cols-1 gap-3",
-                    BlockItem { component_type: ComponentType::OneColumn }
-                    BlockItem { component_type: ComponentType::TwoColumns }
-                    BlockItem { component_type: ComponentType::ThreeColumns }
-                    BlockItem { component_type: ComponentType::FourColumns }
-                    BlockItem { component_type: ComponentType::FiveColumns }
+                    BlockItem { component_type: ComponentType::Grid }
                 }
             }
 
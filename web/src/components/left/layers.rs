@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use crate::components::{canvas_element_id, use_drag_drop_context, DragDropContext, DraggableComponent};
+
+const EYE_ICON: &str = "M15 12a3 3 0 11-6 0 3 3 0 016 0z M2.458 12C3.732 7.943 7.523 5 12 5c4.478 0 8.268 2.943 9.542 7-1.274 4.057-5.064 7-9.542 7-4.477 0-8.268-2.943-9.542-7z";
+const EYE_OFF_ICON: &str = "M13.875 18.825A10.05 10.05 0 0112 19c-4.478 0-8.268-2.943-9.543-7a9.97 9.97 0 011.563-3.029m5.858.908a3 3 0 114.243 4.243M9.878 9.878l4.242 4.242M9.88 9.88l-3.29-3.29m7.532 7.532l3.29 3.29M3 3l3.59 3.59m0 0A9.953 9.953 0 0112 5c4.478 0 8.268 2.943 9.543 7a10.025 10.025 0 01-4.132 5.411m0 0L21 21";
+const LOCK_ICON: &str = "M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z";
+const UNLOCK_ICON: &str = "M8 11V7a4 4 0 118 0m-4 8v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2z";
+
+/// Where a dragged layer would land relative to the row it is over.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DropPosition {
+    Before,
+    After,
+    Inside,
+}
+
+/// The row a dragged layer is over, keyed like the collapsed set.
+#[derive(Clone, Debug, PartialEq)]
+struct DropTarget {
+    node_key: String,
+    position: DropPosition,
+}
+
+/// The open page's component hierarchy: layouts, their columns and the columns' children.
+/// Selecting a layer selects and reveals the component on the canvas, and selecting on the
+/// canvas reveals the layer here. Layers can be dragged to reorder or reparent them, following
+/// the canvas rules: content goes into columns, and layouts go at the top level or into columns
+/// of other layouts, never their own.
+#[component]
+pub fn LayersTree() -> Element {
+    let drag_context = use_drag_drop_context();
+    // Keys of collapsed nodes; everything starts expanded
+    let mut collapsed = use_signal(HashSet::<String>::new);
+    let drop_target = use_signal(|| None::<DropTarget>);
+    let mut revealed = use_signal(|| None::<String>);
+
+    // Follow selections made on the canvas: expand the layer's ancestors and scroll to it
+    use_effect(move || {
+        let selected = drag_context.selected_component_id();
+        if *revealed.peek() == selected {
+            return;
+        }
+        revealed.set(selected.clone());
+        let Some(component_id) = selected else {
+            return;
+        };
+        let mut parent_id = drag_context.get_component(&component_id).and_then(|c| c.parent_id);
+        while let Some(id) = parent_id {
+            collapsed.with_mut(|collapsed| {
+                collapsed.remove(&id);
+                collapsed.retain(|key| !key.starts_with(&format!("{id}-col-")));
+            });
+            parent_id = drag_context.get_component(&id).and_then(|c| c.parent_id);
+        }
+        scroll_into_view(&layer_element_id(&component_id));
+    });
+
+    let roots: Vec<DraggableComponent> = drag_context
+        .get_components_in_drop_zone()
+        .into_iter()
+        .filter(|c| c.parent_id.is_none())
+        .collect();
+
+    rsx! {
+        if roots.is_empty() {
+            p { class: "text-sm text-gray-500", "Nothing on this page yet." }
+        }
+        ul { class: "space-y-1",
+            for component in roots {
+                LayerNode { key: "{component.id}", component, depth: 0, collapsed, drop_target }
+            }
+        }
+    }
+}
+
+#[component]
+fn LayerNode(
+    component: DraggableComponent,
+    depth: usize,
+    collapsed: Signal<HashSet<String>>,
+    drop_target: Signal<Option<DropTarget>>,
+) -> Element {
+    let drag_context = use_drag_drop_context();
+    let is_selected = drag_context.selected_component_id().as_ref() == Some(&component.id);
+    let is_collapsed = collapsed.read().contains(&component.id);
+    let has_children = !component.columns.is_empty();
+    let component_id = component.id.clone();
+    let target_position = drop_target().filter(|target| target.node_key == component.id).map(|target| target.position);
+
+    let (hide_title, hide_icon) = if component.hidden { ("Show", EYE_OFF_ICON) } else { ("Hide", EYE_ICON) };
+    let (lock_title, lock_icon) = if component.locked { ("Unlock", LOCK_ICON) } else { ("Lock", UNLOCK_ICON) };
+
+    rsx! {
+        li {
+            div {
+                id: layer_element_id(&component.id),
+                class: "relative flex items-center p-2 rounded-lg hover:bg-gray-100 cursor-pointer group",
+                class: if is_selected { "bg-blue-50" } else { "" },
+                class: "{drop_indicator_class(target_position)}",
+                style: "padding-left: {depth * 16 + 8}px",
+                draggable: if component.locked { "false" } else { "true" },
+                ondragstart: {
+                    let component_id = component_id.clone();
+                    let locked = component.locked;
+                    move |e: DragEvent| {
+                        if locked {
+                            e.prevent_default();
+                            return;
+                        }
+                        drag_context.set_currently_dragging(Some(component_id.clone()));
+                    }
+                },
+                ondragend: move |_| {
+                    drag_context.set_currently_dragging(None);
+                    drop_target.set(None);
+                },
+                onclick: {
+                    let component_id = component_id.clone();
+                    move |_| {
+                        drag_context.select_component(Some(component_id.clone()));
+                        scroll_into_view(&canvas_element_id(&component_id));
+                    }
+                },
+                ExpandToggle { node_key: component.id.clone(), visible: has_children, collapsed }
+                svg {
+                    class: "w-4 h-4 mr-2 text-gray-500 flex-shrink-0",
+                    fill: "none",
+                    stroke: "currentColor",
+                    view_box: "0 0 24 24",
+                    path {
+                        stroke_linecap: "round",
+                        stroke_linejoin: "round",
+                        stroke_width: "2",
+                        d: component.component_type.icon(),
+                    }
+                }
+                span { class: "truncate", class: if component.hidden { "opacity-50" }, "{component.name}" }
+                span { class: "ml-2 text-xs text-gray-500 shrink-0", "{component.component_type.default_content()}" }
+                div { class: "ml-auto flex items-center gap-1 pl-2",
+                    LayerToggle {
+                        active: component.hidden,
+                        title: hide_title,
+                        icon: hide_icon,
+                        on_toggle: {
+                            let component_id = component_id.clone();
+                            let hidden = component.hidden;
+                            move |_| drag_context.set_component_hidden(&component_id, !hidden)
+                        },
+                    }
+                    LayerToggle {
+                        active: component.locked,
+                        title: lock_title,
+                        icon: lock_icon,
+                        on_toggle: {
+                            let component_id = component_id.clone();
+                            let locked = component.locked;
+                            move |_| drag_context.set_component_locked(&component_id, !locked)
+                        },
+                    }
+                }
+                // Drop targets for the upper and lower half, only present during a drag
+                if drag_context.is_dragging() {
+                    for below in [false, true] {
+                        div {
+                            class: "absolute inset-x-0 h-1/2 z-10",
+                            class: if below { "bottom-0" } else { "top-0" },
+                            ondragover: {
+                                let target = component.clone();
+                                move |e: DragEvent| {
+                                    let position = drag_context
+                                        .dragged_component()
+                                        .filter(|dragged| !drag_context.is_within(&target.id, &dragged.id))
+                                        .and_then(|dragged| drop_position(&dragged, &target, below))
+                                        .filter(|position| {
+                                            // The layout the dragged component would join must not be locked
+                                            let layout_id = match position {
+                                                DropPosition::Inside => Some(&target.id),
+                                                _ => target.parent_id.as_ref(),
+                                            };
+                                            layout_id.is_none_or(|layout_id| drag_context.accepts_children(layout_id))
+                                        });
+                                    if let Some(position) = position {
+                                        e.prevent_default();
+                                        drop_target.set(Some(DropTarget { node_key: target.id.clone(), position }));
+                                    }
+                                }
+                            },
+                            ondragleave: move |_| drop_target.set(None),
+                            ondrop: {
+                                let target = component.clone();
+                                move |e: DragEvent| {
+                                    e.prevent_default();
+                                    if let Some(position) = drop_target.take().map(|target| target.position) {
+                                        drop_on_component(drag_context, &target, position);
+                                    }
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+            if has_children && !is_collapsed {
+                ul { class: "space-y-1",
+                    for column_index in 0..component.columns.len() {
+                        ColumnNode {
+                            key: "{component.id}-col-{column_index}",
+                            layout_id: component.id.clone(),
+                            column_index,
+                            depth: depth + 1,
+                            collapsed,
+                            drop_target,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A column of a layout, listing the components placed in it. Anything dropped on the column
+/// row is added at the end of the column, unless it is the layout itself or one around it.
+#[component]
+fn ColumnNode(
+    layout_id: String,
+    column_index: usize,
+    depth: usize,
+    collapsed: Signal<HashSet<String>>,
+    drop_target: Signal<Option<DropTarget>>,
+) -> Element {
+    let drag_context = use_drag_drop_context();
+    let node_key = format!("{layout_id}-col-{column_index}");
+    let children = drag_context.get_column_children(&layout_id, column_index);
+    let child_count = children.len();
+    let is_collapsed = collapsed.read().contains(&node_key);
+    let target_position = drop_target().filter(|target| target.node_key == node_key).map(|target| target.position);
+
+    rsx! {
+        li {
+            div {
+                class: "relative flex items-center p-2 rounded-lg text-sm text-gray-500",
+                class: "{drop_indicator_class(target_position)}",
+                style: "padding-left: {depth * 16 + 8}px",
+                ExpandToggle { node_key: node_key.clone(), visible: !children.is_empty(), collapsed }
+                "Column {column_index + 1}"
+                if children.is_empty() {
+                    span { class: "ml-2 text-xs text-gray-400", "empty" }
+                }
+                if drag_context.is_dragging() {
+                    div {
+                        class: "absolute inset-0 z-10",
+                        ondragover: {
+                            let node_key = node_key.clone();
+                            let layout_id = layout_id.clone();
+                            move |e: DragEvent| {
+                                let accepts = drag_context.accepts_children(&layout_id)
+                                    && drag_context
+                                        .dragged_component()
+                                        .is_some_and(|dragged| !drag_context.is_within(&layout_id, &dragged.id));
+                                if accepts {
+                                    e.prevent_default();
+                                    drop_target.set(Some(DropTarget { node_key: node_key.clone(), position: DropPosition::Inside }));
+                                }
+                            }
+                        },
+                        ondragleave: move |_| drop_target.set(None),
+                        ondrop: move |e| {
+                            e.prevent_default();
+                            drop_target.set(None);
+                            if let Some(dragged_id) = drag_context.take_currently_dragging() {
+                                drag_context.insert_into_column(&dragged_id, &layout_id, column_index, child_count);
+                            }
+                        },
+                    }
+                }
+            }
+            if !is_collapsed {
+                ul { class: "space-y-1",
+                    for component in children {
+                        LayerNode { key: "{component.id}", component, depth: depth + 1, collapsed, drop_target }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Eye or lock button on a layer row. Shown on hover, and always while `active`.
+#[component]
+fn LayerToggle(active: bool, title: &'static str, icon: &'static str, on_toggle: EventHandler) -> Element {
+    rsx! {
+        button {
+            class: "p-0.5 rounded text-gray-400 hover:text-gray-800 hover:bg-gray-200",
+            class: if active { "text-gray-700" } else { "opacity-0 group-hover:opacity-100" },
+            title,
+            onclick: move |e| {
+                e.stop_propagation();
+                on_toggle.call(());
+            },
+            svg {
+                class: "w-4 h-4",
+                fill: "none",
+                stroke: "currentColor",
+                view_box: "0 0 24 24",
+                path {
+                    stroke_linecap: "round",
+                    stroke_linejoin: "round",
+                    stroke_width: "2",
+                    d: icon,
+                }
+            }
+        }
+    }
+}
+
+/// Chevron that expands or collapses `node_key`. Keeps its space when hidden so rows line up.
+#[component]
+fn ExpandToggle(node_key: String, visible: bool, collapsed: Signal<HashSet<String>>) -> Element {
+    let is_collapsed = collapsed.read().contains(&node_key);
+
+    rsx! {
+        button {
+            class: "w-4 h-4 mr-1 flex-shrink-0 text-gray-400 hover:text-gray-700 text-xs leading-none",
+            class: if !visible { "invisible" },
+            onclick: move |e| {
+                e.stop_propagation();
+                collapsed.with_mut(|collapsed| {
+                    if !collapsed.remove(&node_key) {
+                        collapsed.insert(node_key.clone());
+                    }
+                });
+            },
+            if is_collapsed { "▸" } else { "▾" }
+        }
+    }
+}
+
+/// Where `dragged` may be dropped on the row of `target`, given which half of the row the
+/// pointer is over. Content dropped on a layout goes into its first column; anything else is
+/// placed next to the target. Callers rule out targets nested inside `dragged`.
+fn drop_position(dragged: &DraggableComponent, target: &DraggableComponent, below: bool) -> Option<DropPosition> {
+    if dragged.id == target.id {
+        return None;
+    }
+    if !dragged.component_type.is_layout() && target.component_type.is_layout() {
+        Some(DropPosition::Inside)
+    } else if below {
+        Some(DropPosition::After)
+    } else {
+        Some(DropPosition::Before)
+    }
+}
+
+fn drop_on_component(drag_context: DragDropContext, target: &DraggableComponent, position: DropPosition) {
+    let Some(dragged_id) = drag_context.take_currently_dragging() else {
+        return;
+    };
+    let Some(dragged) = drag_context.get_component(&dragged_id) else {
+        return;
+    };
+    let after = position == DropPosition::After;
+
+    if position == DropPosition::Inside {
+        let end = drag_context.get_column_children(&target.id, 0).len();
+        drag_context.insert_into_column(&dragged_id, &target.id, 0, end);
+    } else if let Some((layout_id, column_index, index)) = drag_context.position_in_column(&target.id) {
+        drag_context.insert_into_column(&dragged_id, &layout_id, column_index, index + after as usize);
+    } else if dragged.component_type.is_layout() {
+        // Layouts fresh from the blocks panel or out of a column are taken onto the canvas too
+        let Some(index) = drag_context
+            .get_components_in_drop_zone()
+            .into_iter()
+            .filter(|c| c.parent_id.is_none() && c.id != dragged_id)
+            .position(|c| c.id == target.id)
+        else {
+            return;
+        };
+        drag_context.move_to_root_index(&dragged_id, index + after as usize);
+    }
+}
+
+fn drop_indicator_class(position: Option<DropPosition>) -> &'static str {
+    match position {
+        Some(DropPosition::Before) => "shadow-[inset_0_2px_0_0_#3b82f6]",
+        Some(DropPosition::After) => "shadow-[inset_0_-2px_0_0_#3b82f6]",
+        Some(DropPosition::Inside) => "ring-2 ring-blue-400 bg-blue-50",
+        None => "",
+    }
+}
+
+fn layer_element_id(component_id: &str) -> String {
+    format!("layer-{component_id}")
+}
+
+/// Smoothly scrolls the element with `element_id` into view, if it is on the page.
+fn scroll_into_view(element_id: &str) {
+    let eval = document::eval(
+        r#"
+        const id = await dioxus.recv();
+        document.getElementById(id)?.scrollIntoView({ behavior: "smooth", block: "nearest" });
+        "#,
+    );
+    if let Err(err) = eval.send(element_id) {
+        log::warn!("Could not scroll to {element_id}: {err:?}");
+    }
+}
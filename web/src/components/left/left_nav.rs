@@ -1,5 +1,7 @@
 use dioxus::prelude::*;
-use super::{BlockItem, BlocksPanel, StylingPanel, PagesLayersPanel, AssetPanel};
+use super::{BlockItem, BlocksPanel, StylingPanel, PagesLayersPanel, AssetPanel, ProjectsPanel};
+use super::projects::save_project;
+use crate::components::{use_drag_drop_context, SaveStatus};
 
 
 #[derive(Clone, PartialEq)]
@@ -9,6 +11,7 @@ enum NavSection {
     Styling,
     Templates,
     Assets,
+    Projects,
     Settings,
 }
 
@@ -16,6 +19,7 @@ enum NavSection {
 pub fn LeftNav() -> Element {
     let mut active_section = use_signal(|| NavSection::Blocks);
     let mut is_drawer_open = use_signal(|| false);
+    let drag_context = use_drag_drop_context();
     
     rsx! {
         div { class: "flex h-screen z-50  bg-gray-50",
@@ -162,10 +166,27 @@ pub fn LeftNav() -> Element {
 
                 }
                 // Settings/Save Section
-                div { class: "p-4 border-t border-gray-200 flex justify-center",
+                div { class: "p-4 border-t border-gray-200 flex flex-col items-center gap-1",
                     button {
                         class: "p-2 rounded-lg bg-blue-600 text-white hover:bg-blue-700",
                         title: "Save Project",
+                        onclick: move |_| async move {
+                            drag_context.save_now();
+                            // Without a name, open the Projects panel to pick one
+                            let Some(name) = drag_context.project_name() else {
+                                active_section.set(NavSection::Projects);
+                                is_drawer_open.set(true);
+                                return;
+                            };
+                            let status = match save_project(name, drag_context.document()).await {
+                                Ok(summary) => SaveStatus::Saved(summary.updated_at.with_timezone(&chrono::Local)),
+                                Err(err) => {
+                                    log::error!("Failed to save project: {err}");
+                                    SaveStatus::Failed(err.to_string())
+                                }
+                            };
+                            drag_context.set_save_status(status);
+                        },
                         svg {
                             class: "w-5 h-5",
                             fill: "none",
@@ -179,6 +200,26 @@ pub fn LeftNav() -> Element {
                             }
                         }
                     }
+                    // Last saved indicator
+                    match drag_context.save_status() {
+                        SaveStatus::Unchanged => rsx! {
+                            span { class: "text-[10px] text-gray-400 text-center leading-tight", "No changes" }
+                        },
+                        SaveStatus::Saved(at) => {
+                            let full_time = at.format("%Y-%m-%d %H:%M:%S");
+                            let short_time = at.format("%H:%M");
+                            rsx! {
+                                span {
+                                    class: "text-[10px] text-gray-500 text-center leading-tight",
+                                    title: "Last saved {full_time}",
+                                    "Saved {short_time}"
+                                }
+                            }
+                        }
+                        SaveStatus::Failed(err) => rsx! {
+                            span { class: "text-[10px] text-red-500 text-center leading-tight", title: "{err}", "Save failed" }
+                        },
+                    }
                 }
             }
             // Sliding Drawer for Panels
@@ -201,6 +242,7 @@ pub fn LeftNav() -> Element {
                             NavSection::Styling => "Styling",
                             NavSection::Templates => "Templates",
                             NavSection::Assets => "Assets",
+                            NavSection::Projects => "Projects",
                             NavSection::Settings => "Settings",
                         }
                     }
@@ -239,6 +281,9 @@ pub fn LeftNav() -> Element {
                         NavSection::Assets => rsx! {
                             AssetPanel {}
                         },
+                        NavSection::Projects => rsx! {
+                            ProjectsPanel {}
+                        },
                         NavSection::Settings => rsx! {
                             SettingsPanel {}
                         },
@@ -10,5 +10,13 @@ pub use styling::StylingPanel;
 mod pages_layers;   
 pub use pages_layers::PagesLayersPanel;
 
+mod layers;
+use layers::LayersTree;
+
 mod assets;
-pub use assets::AssetPanel;
\ No newline at end of file
+pub use assets::AssetPanel;
+#[cfg(feature = "server")]
+pub use assets::store::library_dir;
+
+mod projects;
+pub use projects::ProjectsPanel;
\ No newline at end of file
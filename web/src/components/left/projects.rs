@@ -0,0 +1,483 @@
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+use dioxus::prelude::server_fn::codec::Json;
+use serde::{Deserialize, Serialize};
+use crate::components::{download_file, file_stem, use_drag_drop_context, Document, ProjectFile};
+
+/// A project as listed by [`list_projects`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub name: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[component]
+pub fn ProjectsPanel() -> Element {
+    let drag_context = use_drag_drop_context();
+    let mut projects = use_resource(list_projects);
+    let mut project_name = use_signal(|| drag_context.project_name().unwrap_or_default());
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let save = move |_| async move {
+        let name = project_name().trim().to_string();
+        if name.is_empty() {
+            status.set(Some(Err("Give the project a name first".into())));
+            return;
+        }
+        match save_project(name.clone(), drag_context.document()).await {
+            Ok(summary) => {
+                drag_context.set_project_name(Some(summary.name.clone()));
+                status.set(Some(Ok(format!("Saved \"{}\"", summary.name))));
+                projects.restart();
+            }
+            Err(err) => status.set(Some(Err(err.to_string()))),
+        }
+    };
+
+    let download = move |_| async move {
+        let name = project_name().trim().to_string();
+        let stem = match file_stem(&name) {
+            stem if stem.is_empty() => "project".to_string(),
+            stem => stem,
+        };
+        let project = ProjectFile::new(Some(name).filter(|name| !name.is_empty()), &drag_context.document());
+        let saved = match project.to_json() {
+            Ok(json) => download_file(&format!("{stem}.json"), "application/json", &json).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = saved {
+            status.set(Some(Err(err)));
+        }
+    };
+
+    let open = move |e: FormEvent| async move {
+        let Some(files) = e.files() else { return };
+        let Some(file_name) = files.files().into_iter().next() else { return };
+        let Some(json) = files.read_file_to_string(&file_name).await else {
+            status.set(Some(Err(format!("Could not read {file_name}"))));
+            return;
+        };
+        match ProjectFile::from_json(&json) {
+            Ok(project) => {
+                let name = project.name.clone();
+                drag_context.replace_document(project.into_document());
+                drag_context.set_project_name(name.clone());
+                project_name.set(name.unwrap_or_default());
+                status.set(Some(Ok(format!("Opened {file_name}"))));
+            }
+            Err(err) => status.set(Some(Err(err))),
+        }
+    };
+
+    rsx! {
+        div { class: "space-y-6",
+            // Save current document
+            div { class: "space-y-2",
+                label { class: "block text-sm font-medium text-gray-900", "Project name" }
+                div { class: "flex space-x-2",
+                    input {
+                        class: "flex-1 min-w-0 p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
+                        placeholder: "My landing page",
+                        value: "{project_name}",
+                        oninput: move |e| project_name.set(e.value()),
+                    }
+                    button {
+                        class: "px-3 py-2 bg-blue-600 text-white rounded hover:bg-blue-700 text-sm",
+                        onclick: save,
+                        "Save"
+                    }
+                }
+                match status() {
+                    Some(Ok(message)) => rsx! {
+                        p { class: "text-xs text-green-600", "{message}" }
+                    },
+                    Some(Err(message)) => rsx! {
+                        p { class: "text-xs text-red-600", "{message}" }
+                    },
+                    None => rsx! {},
+                }
+            }
+            // Project files
+            div { class: "flex space-x-2",
+                button {
+                    class: "flex-1 px-3 py-2 border border-gray-300 rounded hover:bg-gray-50 text-sm",
+                    onclick: download,
+                    "Download project"
+                }
+                label { class: "flex-1 px-3 py-2 border border-gray-300 rounded hover:bg-gray-50 text-sm text-center cursor-pointer",
+                    "Open project"
+                    input {
+                        r#type: "file",
+                        class: "hidden",
+                        accept: ".json,application/json",
+                        onchange: open,
+                    }
+                }
+            }
+            // Projects stored on the server
+            div {
+                h3 { class: "font-medium mb-2", "Shared projects" }
+                match &*projects.read() {
+                    None => rsx! {
+                        p { class: "text-sm text-gray-500", "Loading projects..." }
+                    },
+                    Some(Err(err)) => rsx! {
+                        p { class: "text-sm text-red-600", "Could not load projects: {err}" }
+                    },
+                    Some(Ok(list)) if list.is_empty() => rsx! {
+                        p { class: "text-sm text-gray-500", "No projects saved on this server yet." }
+                    },
+                    Some(Ok(list)) => rsx! {
+                        ul { class: "space-y-2",
+                            for project in list.clone() {
+                                ProjectRow {
+                                    key: "{project.name}",
+                                    project,
+                                    on_open: move |name: String| async move {
+                                        match load_project(name.clone()).await {
+                                            Ok(document) => {
+                                                drag_context.replace_document(document);
+                                                drag_context.set_project_name(Some(name.clone()));
+                                                project_name.set(name.clone());
+                                                status.set(Some(Ok(format!("Opened \"{name}\""))));
+                                            }
+                                            Err(err) => status.set(Some(Err(err.to_string()))),
+                                        }
+                                    },
+                                    on_delete: move |name: String| async move {
+                                        match delete_project(name.clone()).await {
+                                            Ok(()) => status.set(Some(Ok(format!("Deleted \"{name}\"")))),
+                                            Err(err) => status.set(Some(Err(err.to_string()))),
+                                        }
+                                        projects.restart();
+                                    },
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// One stored project, with Open and a two-step Delete.
+#[component]
+fn ProjectRow(project: ProjectSummary, on_open: EventHandler<String>, on_delete: EventHandler<String>) -> Element {
+    let mut confirming_delete = use_signal(|| false);
+    let updated_at = project.updated_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+    let name_for_open = project.name.clone();
+    let name_for_delete = project.name.clone();
+
+    rsx! {
+        li { class: "p-2 rounded-lg border border-gray-200 hover:bg-gray-50",
+            div { class: "flex justify-between items-center",
+                div { class: "min-w-0",
+                    p { class: "text-sm font-medium truncate", "{project.name}" }
+                    p { class: "text-xs text-gray-500", "{updated_at}" }
+                }
+                if confirming_delete() {
+                    div { class: "flex space-x-2 text-xs",
+                        button {
+                            class: "text-red-600 hover:text-red-800 font-medium",
+                            onclick: move |_| {
+                                confirming_delete.set(false);
+                                on_delete.call(name_for_delete.clone());
+                            },
+                            "Delete"
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-gray-700",
+                            onclick: move |_| confirming_delete.set(false),
+                            "Cancel"
+                        }
+                    }
+                } else {
+                    div { class: "flex space-x-2 text-xs",
+                        button {
+                            class: "text-blue-600 hover:text-blue-800 font-medium",
+                            onclick: move |_| on_open.call(name_for_open.clone()),
+                            "Open"
+                        }
+                        button {
+                            class: "text-red-500 hover:text-red-700",
+                            onclick: move |_| confirming_delete.set(true),
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[server]
+async fn list_projects() -> Result<Vec<ProjectSummary>, ServerFnError> {
+    let mut projects = store::read_all(&store::projects_dir())?;
+    projects.sort_by_key(|project| std::cmp::Reverse(project.updated_at));
+    Ok(projects)
+}
+
+#[server]
+async fn load_project(name: String) -> Result<Document, ServerFnError> {
+    Ok(store::read(&store::projects_dir(), &name)?.into_document())
+}
+
+// JSON, since the default URL encoding can't nest as deep as a document and drops empty lists
+#[server(input = Json)]
+pub(super) async fn save_project(name: String, document: Document) -> Result<ProjectSummary, ServerFnError> {
+    let name = name.trim().to_string();
+    store::write(&store::projects_dir(), &ProjectFile::new(Some(name.clone()), &document))?;
+    Ok(ProjectSummary {
+        name,
+        updated_at: Utc::now(),
+    })
+}
+
+#[server]
+async fn delete_project(name: String) -> Result<(), ServerFnError> {
+    store::delete(&store::projects_dir(), &name)?;
+    Ok(())
+}
+
+/// One project file per project in `DRAGNDROP_PROJECTS_DIR` (default `./projects`), named
+/// after the project.
+#[cfg(any(feature = "server", test))]
+pub(super) mod store {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, PoisonError};
+
+    use chrono::{DateTime, Utc};
+    use dioxus::prelude::ServerFnError;
+
+    use super::ProjectSummary;
+    use crate::components::{file_stem, ProjectFile};
+
+    /// Held while a project file is checked and replaced or removed.
+    static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+    #[cfg(feature = "server")]
+    pub fn projects_dir() -> PathBuf {
+        std::env::var_os("DRAGNDROP_PROJECTS_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("projects"))
+    }
+
+    fn project_path(dir: &Path, name: &str) -> Result<PathBuf, ServerFnError> {
+        let stem = file_stem(name);
+        if stem.is_empty() {
+            return Err(ServerFnError::new("Project names need at least one letter or digit"));
+        }
+        Ok(dir.join(format!("{stem}.json")))
+    }
+
+    fn parse(json: &str) -> Result<ProjectFile, ServerFnError> {
+        ProjectFile::from_json(json).map_err(ServerFnError::new)
+    }
+
+    /// The name a project file is listed under: its own, or its file name if it has none.
+    fn stored_name(path: &Path, project: &ProjectFile) -> String {
+        project
+            .name
+            .clone()
+            .unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().into())
+    }
+
+    /// The file `name` is stored in, and the project in it. Names can share a file ("My Site"
+    /// and "my-site"), so the project may be another one.
+    fn stored(dir: &Path, name: &str) -> Result<(PathBuf, Option<ProjectFile>), ServerFnError> {
+        let path = project_path(dir, name)?;
+        match std::fs::read_to_string(&path) {
+            Ok(json) => Ok((path, Some(parse(&json)?))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok((path, None)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Paths of the project files in `dir`.
+    fn project_files(dir: &Path) -> Result<Vec<PathBuf>, ServerFnError> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    pub fn read_all(dir: &Path) -> Result<Vec<ProjectSummary>, ServerFnError> {
+        let mut projects = Vec::new();
+        for path in project_files(dir)? {
+            let project = std::fs::read_to_string(&path)
+                .map_err(ServerFnError::from)
+                .and_then(|json| parse(&json));
+            let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified());
+            match (project, modified) {
+                (Ok(project), Ok(modified)) => projects.push(ProjectSummary {
+                    name: stored_name(&path, &project),
+                    updated_at: DateTime::<Utc>::from(modified),
+                }),
+                _ => log::warn!("Skipping unreadable project file {}", path.display()),
+            }
+        }
+        Ok(projects)
+    }
+
+    /// Names of the saved projects with `asset_id` among their assets. A project file that
+    /// can't be read counts as holding it, so nothing is deleted from under it.
+    pub fn projects_using_asset(dir: &Path, asset_id: &str) -> Result<Vec<String>, ServerFnError> {
+        let mut names = Vec::new();
+        for path in project_files(dir)? {
+            match std::fs::read_to_string(&path).map_err(ServerFnError::from).and_then(|json| parse(&json)) {
+                Ok(project) => {
+                    let name = stored_name(&path, &project);
+                    if project.into_document().assets.iter().any(|asset| asset.id == asset_id) {
+                        names.push(name);
+                    }
+                }
+                Err(_) => names.push(path.file_name().unwrap_or_default().to_string_lossy().into()),
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn read(dir: &Path, name: &str) -> Result<ProjectFile, ServerFnError> {
+        match stored(dir, name)? {
+            (path, Some(project)) if stored_name(&path, &project) == name => Ok(project),
+            _ => Err(ServerFnError::new(format!("No project named \"{name}\""))),
+        }
+    }
+
+    /// Saves `project`, refusing to replace a different project stored under the same file
+    /// name. A failed save leaves the previous version whole.
+    pub fn write(dir: &Path, project: &ProjectFile) -> Result<(), ServerFnError> {
+        let name = project.name.as_deref().unwrap_or_default();
+        let json = project.to_json().map_err(ServerFnError::new)?;
+
+        let _lock = STORE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let (path, existing) = stored(dir, name)?;
+        if let Some(existing) = existing {
+            let existing_name = stored_name(&path, &existing);
+            if existing_name != name {
+                return Err(ServerFnError::new(format!(
+                    "\"{name}\" would replace the project \"{existing_name}\"; pick another name"
+                )));
+            }
+        }
+        std::fs::create_dir_all(dir)?;
+        write_atomic(&path, json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes `contents` aside and renames them over `path`, so readers see the old file or
+    /// the new one, never half of it.
+    pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let temp = path.with_file_name(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp);
+        })
+    }
+
+    pub fn delete(dir: &Path, name: &str) -> Result<(), ServerFnError> {
+        let _lock = STORE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        read(dir, name)?;
+        std::fs::remove_file(project_path(dir, name)?)?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::components::{Asset, Document};
+
+        fn project(name: &str, document: &Document) -> ProjectFile {
+            ProjectFile::new(Some(name.into()), document)
+        }
+
+        #[test]
+        fn projects_are_saved_listed_and_deleted() {
+            let dir = std::env::temp_dir().join(format!("dragndrop-projects-{}", uuid::Uuid::new_v4().simple()));
+            let mut document = Document::starter();
+
+            write(&dir, &project("My Site", &document)).unwrap();
+            document.add_page("About");
+            write(&dir, &project("My Site", &document)).unwrap();
+            write(&dir, &project("Blog", &Document::new())).unwrap();
+
+            let mut names: Vec<String> = read_all(&dir).unwrap().into_iter().map(|project| project.name).collect();
+            names.sort();
+            assert_eq!(names, ["Blog", "My Site"]);
+            assert_eq!(read(&dir, "My Site").unwrap().into_document().pages, document.pages);
+            // Nothing but the project files is left behind
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+            document.add_asset(Asset {
+                id: "asset_abc".into(),
+                name: "logo.png".into(),
+                file_type: "image/png".into(),
+                preview_url: "/library/abc.png".into(),
+                size: "1 KB".into(),
+            });
+            write(&dir, &project("Blog", &document)).unwrap();
+            assert_eq!(projects_using_asset(&dir, "asset_abc").unwrap(), ["Blog"]);
+            assert!(projects_using_asset(&dir, "asset_def").unwrap().is_empty());
+
+            delete(&dir, "Blog").unwrap();
+            assert!(read(&dir, "Blog").is_err());
+            assert_eq!(read_all(&dir).unwrap().len(), 1);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+
+        #[test]
+        fn names_sharing_a_file_do_not_replace_each_other() {
+            let dir = std::env::temp_dir().join(format!("dragndrop-projects-{}", uuid::Uuid::new_v4().simple()));
+            let document = Document::starter();
+            write(&dir, &project("My Site", &document)).unwrap();
+
+            assert!(write(&dir, &project("my-site", &Document::new())).is_err());
+            assert!(read(&dir, "my-site").is_err());
+            assert!(delete(&dir, "my-site").is_err());
+            assert_eq!(read(&dir, "My Site").unwrap().into_document().pages, document.pages);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dioxus::prelude::server_fn::ServerFn;
+
+    use super::*;
+    use crate::components::ComponentType;
+
+    fn takes_json<T: ServerFn<InputEncoding = Json>>() {}
+
+    #[test]
+    fn documents_reach_the_server_intact() {
+        takes_json::<SaveProject>();
+
+        let mut document = Document::starter();
+        let outer = document.add_component(ComponentType::Grid);
+        document.move_to_drop_zone(&outer, (0.0, 0.0));
+        let inner = document.add_component(ComponentType::Grid);
+        document.add_to_column(&inner, &outer, 1);
+        let button = document.add_component(ComponentType::Button);
+        document.add_to_column(&button, &inner, 0);
+        document.add_component_styles(&button, "px-8 md:px-4");
+        document.set_component_attribute(&button, "aria-label", "Sign up");
+        document.add_page("About");
+
+        let json = serde_json::to_string(&SaveProject { name: "Site".into(), document: document.clone() }).unwrap();
+        let received: SaveProject = serde_json::from_str(&json).unwrap();
+        assert_eq!(received.name, "Site");
+        assert_eq!(received.document, document);
+    }
+}
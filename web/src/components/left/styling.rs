@@ -1,101 +1,72 @@
 use dioxus::prelude::*;
-use log;
-
-#[derive(Clone, PartialEq, Debug)]
-struct ThemeSettings {
-    primary_color: String,
-    secondary_color: String,
-    accent_color: String,
-    button_color: String,
-    link_color: String,
-    text_color: String,
-    background_color: String,
-    custom_classes: String,
-}
+use crate::components::{
+    color_hex, normalize_color, shade_scale, use_drag_drop_context, ColorScheme, ContrastPair, SaveStatus, ThemeSettings, ThemeToken,
+    AAA_CONTRAST, AA_CONTRAST, PALETTE, SHADES,
+};
 
 #[component]
 pub fn StylingPanel() -> Element {
-    let mut settings = use_signal(|| ThemeSettings {
-        primary_color: "blue-600".into(),
-        secondary_color: "gray-600".into(),
-        accent_color: "indigo-600".into(),
-        button_color: "blue-600".into(),
-        link_color: "blue-600".into(),
-        text_color: "gray-800".into(),
-        background_color: "white".into(),
-        custom_classes: "".into(),
-    });
-
-    let color_options = vec![
-        "slate", "gray", "zinc", "neutral", "stone", 
-        "red", "orange", "amber", "yellow", "lime",
-        "green", "emerald", "teal", "cyan", "sky",
-        "blue", "indigo", "violet", "purple", "fuchsia",
-        "pink", "rose"
-    ];
-
-    let intensity_options = vec!["50", "100", "200", "300", "400", "500", "600", "700", "800", "900"];
+    let drag_context = use_drag_drop_context();
+    let settings = drag_context.theme();
+    // The palette previewed on the canvas is the one edited here
+    let scheme = drag_context.preview_scheme();
+    let colors = settings.scheme(scheme);
+    // Shown once "Save Theme" has been pressed
+    let mut show_save_status = use_signal(|| false);
 
     rsx! {
         div { class: "p-6 space-y-6",
             h2 { class: "text-2xl font-bold", "Theme Customization" }
-            // Color Selection Grid
-            div { class: "grid grid-cols-1 md:grid-cols-2 gap-6",
-                // Primary Color
-                ColorSelector {
-                    label: "Primary Color",
-                    current_value: settings().primary_color.clone(),
-                    on_change: move |value| settings.write().primary_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
-                }
-                // Secondary Color
-                ColorSelector {
-                    label: "Secondary Color",
-                    current_value: settings().secondary_color.clone(),
-                    on_change: move |value| settings.write().secondary_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
-                }
-                // Accent Color
-                ColorSelector {
-                    label: "Accent Color",
-                    current_value: settings().accent_color.clone(),
-                    on_change: move |value| settings.write().accent_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
-                }
-                // Button Color
-                ColorSelector {
-                    label: "Button Color",
-                    current_value: settings().button_color.clone(),
-                    on_change: move |value| settings.write().button_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
+            // Palette
+            div { class: "flex items-center gap-3",
+                div { class: "inline-flex rounded-lg border border-gray-300 overflow-hidden",
+                    for option in ColorScheme::ALL {
+                        button {
+                            class: "px-3 py-1.5 text-sm",
+                            class: if option == scheme { "bg-gray-800 text-white" } else { "text-gray-600 hover:bg-gray-100" },
+                            onclick: move |_| drag_context.set_preview_scheme(option),
+                            "{option.label()}"
+                        }
+                    }
                 }
-                // Link Color
-                ColorSelector {
-                    label: "Link Color",
-                    current_value: settings().link_color.clone(),
-                    on_change: move |value| settings.write().link_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
+                p { class: "text-sm text-gray-500",
+                    match scheme {
+                        ColorScheme::Light => "Editing the light palette",
+                        ColorScheme::Dark => "Editing the dark palette, used when the visitor's system is in dark mode",
+                    }
                 }
-                // Text Color
-                ColorSelector {
-                    label: "Text Color",
-                    current_value: settings().text_color.clone(),
-                    on_change: move |value| settings.write().text_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
+            }
+            // Color Selection Grid
+            div { class: "grid grid-cols-1 md:grid-cols-2 gap-6",
+                for token in ThemeToken::ALL {
+                    div { key: "{token.name()}",
+                        ColorSelector {
+                            label: token.label(),
+                            current_value: colors.color(token).to_string(),
+                            on_change: move |value| drag_context.update_theme(token.field(scheme), move |theme| theme.set_color(scheme, token, value)),
+                        }
+                        // Buttons and links can follow the primary color
+                        if matches!(token, ThemeToken::Button | ThemeToken::Link) {
+                            FollowPrimary {
+                                follows: colors.source(token) != token,
+                                on_change: move |follows: bool| {
+                                    drag_context.update_theme(token.field(scheme), move |theme| {
+                                        let color = if follows { String::new() } else { theme.scheme(scheme).primary_color };
+                                        theme.set_color(scheme, token, color);
+                                    })
+                                },
+                            }
+                        }
+                    }
                 }
-                // Background Color
-                ColorSelector {
-                    label: "Background Color",
-                    current_value: settings().background_color.clone(),
-                    on_change: move |value| settings.write().background_color = value,
-                    color_options: color_options.clone(),
-                    intensity_options: intensity_options.clone(),
+            }
+            // Contrast
+            div {
+                h3 { class: "text-lg font-semibold mb-2", "Contrast" }
+                ul { class: "border border-gray-200 rounded-lg divide-y divide-gray-200",
+                    for pair in ContrastPair::ALL {
+                        ContrastRow { key: "{pair.label}", pair, scheme, colors: colors.clone() }
+                    }
                 }
             }
             // Custom Tailwind Classes
@@ -105,61 +76,73 @@ pub fn StylingPanel() -> Element {
                     class: "w-full p-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-blue-500",
                     placeholder: "Enter additional Tailwind classes (e.g., rounded-lg shadow-md)",
                     rows: "3",
-                    value: "{settings().custom_classes}",
-                    oninput: move |e| settings.write().custom_classes = e.value().clone(),
+                    value: "{settings.custom_classes}",
+                    oninput: move |e| drag_context.update_theme("custom_classes", |theme| theme.custom_classes = e.value()),
                 }
             }
             // Preview Section
             div { class: "mt-8 p-4 border rounded-lg",
                 h3 { class: "text-lg font-semibold mb-4", "Theme Preview" }
                 div {
-                    class: "space-y-4",
-                    style: "background-color: var(--background-color)",
+                    class: "space-y-4 p-4 rounded bg-[var(--theme-background)]",
+                    style: colors.css_variables(),
                     // Preview Button
-                    button { class: "px-4 py-2 rounded text-white bg-{settings().button_color} hover:bg-{settings().button_color}-700",
+                    button { class: "px-4 py-2 rounded text-white bg-[var(--theme-button)] hover:opacity-90",
                         "Sample Button"
                     }
                     // Preview Link
                     a {
-                        class: "text-{settings().link_color} hover:underline",
+                        class: "block text-[var(--theme-link)] hover:underline",
                         href: "#",
                         "Sample Link"
                     }
                     // Preview Text
-                    p { class: "text-{settings().text_color}",
+                    p { class: "text-[var(--theme-text)]",
                         "Sample text showing the current text color settings"
                     }
+                    // Preview Hero
+                    div { class: "p-4 rounded text-white bg-gradient-to-r from-[var(--theme-primary)] to-[var(--theme-accent)]",
+                        "Hero"
+                    }
                     // Preview Card
-                    div { class: "p-4 border rounded-lg bg-{settings().background_color} border-{settings().secondary_color}",
-                        h4 { class: "text-lg font-semibold text-{settings().primary_color}",
+                    div { class: "p-4 border rounded-lg bg-[var(--theme-background)] border-[var(--theme-secondary)]",
+                        h4 { class: "text-lg font-semibold text-[var(--theme-primary)]",
                             "Card Title"
                         }
-                        p { class: "text-{settings().text_color}",
+                        p { class: "text-[var(--theme-text)]",
                             "This card shows how your colors work together"
                         }
                     }
                 }
             }
             // Action Buttons
-            div { class: "flex justify-end space-x-4 mt-6",
+            div { class: "flex justify-end items-center space-x-4 mt-6",
+                if show_save_status() {
+                    match drag_context.save_status() {
+                        SaveStatus::Saved(at) => {
+                            let time = at.format("%H:%M");
+                            rsx! {
+                                span { class: "text-sm text-green-600", "Saved with the project at {time}" }
+                            }
+                        }
+                        SaveStatus::Failed(err) => rsx! {
+                            span { class: "text-sm text-red-600", "Could not save: {err}" }
+                        },
+                        SaveStatus::Unchanged => rsx! {},
+                    }
+                }
                 button {
                     class: "px-4 py-2 bg-gray-200 text-gray-800 rounded-lg hover:bg-gray-300",
                     onclick: move |_| {
-                        settings.write().primary_color = "blue-600".into();
-                        settings.write().secondary_color = "gray-600".into();
-                        settings.write().accent_color = "indigo-600".into();
-                        settings.write().button_color = "blue-600".into();
-                        settings.write().link_color = "blue-600".into();
-                        settings.write().text_color = "gray-800".into();
-                        settings.write().background_color = "white".into();
-                        settings.write().custom_classes = "".into();
+                        drag_context.set_theme(ThemeSettings::default());
                     },
                     "Reset to Defaults"
                 }
                 button {
                     class: "px-4 py-2 bg-blue-600 text-white rounded-lg hover:bg-blue-700",
                     onclick: move |_| {
-                        log::info!("Saved theme settings: {:?}", settings.read());
+                        drag_context.save_now();
+                        show_save_status.set(true);
                     },
                     "Save Theme"
                 }
@@ -168,25 +151,112 @@ pub fn StylingPanel() -> Element {
     }
 }
 
+/// A text and background pair with its contrast ratio, whether it meets WCAG AA and AAA, and the
+/// nearest shade that would when it doesn't.
 #[component]
-fn ColorSelector(
-    label: String,
-    current_value: String,
-    on_change: EventHandler<String>,
-    color_options: Vec<&'static str>,
-    intensity_options: Vec<&'static str>,
-) -> Element {
-    let (current_color, current_intensity) = {
-        let parts: Vec<&str> = current_value.split('-').collect();
-        if parts.len() == 2 {
-            (parts[0].to_string(), parts[1].to_string())
-        } else {
-            ("blue".into(), "600".into())
-        }
+fn ContrastRow(pair: ContrastPair, scheme: ColorScheme, colors: ThemeSettings) -> Element {
+    let drag_context = use_drag_drop_context();
+    let Some(ratio) = colors.contrast(pair) else {
+        return rsx! {};
+    };
+    let foreground = pair.foreground.map_or_else(|| "#ffffff".to_string(), |token| colors.css_value(token));
+    let background = colors.css_value(pair.background);
+    let ratio_text = format!("{ratio:.2}:1");
+    // Passing levels in green; failing AA is an error, failing only AAA a warning
+    let passed = "px-1.5 py-0.5 rounded text-xs font-medium bg-green-100 text-green-800";
+    let aa_class = if ratio >= AA_CONTRAST { passed } else { "px-1.5 py-0.5 rounded text-xs font-medium bg-red-100 text-red-800" };
+    let aaa_class = if ratio >= AAA_CONTRAST { passed } else { "px-1.5 py-0.5 rounded text-xs font-medium bg-amber-100 text-amber-800" };
+
+    // The first level missed and the nearest shade reaching it, applied to the color that
+    // actually holds it (the primary, for buttons and links following it)
+    let target = colors.source(pair.adjusted());
+    let fix = if ratio < AA_CONTRAST {
+        colors.contrast_fix(pair, AA_CONTRAST).map(|color| ("AA", color))
+    } else if ratio < AAA_CONTRAST {
+        colors.contrast_fix(pair, AAA_CONTRAST).map(|color| ("AAA", color))
+    } else {
+        None
     };
 
-    let current_color_clone = current_color.clone();
-    let current_intensity_clone = current_intensity.clone();
+    rsx! {
+        li { class: "flex items-center gap-3 p-2 text-sm",
+            span {
+                class: "px-2 py-1 rounded border border-gray-200 font-medium",
+                style: "color: {foreground}; background-color: {background}",
+                "Aa"
+            }
+            span { class: "flex-1 text-gray-700", "{pair.label}" }
+            span { class: "font-mono text-xs text-gray-600", "{ratio_text}" }
+            span { class: aa_class, "AA" }
+            span { class: aaa_class, "AAA" }
+            match fix {
+                Some((level, color)) => {
+                    let hint = format!("Change the {} color to {color} to pass {level}", target.name());
+                    rsx! {
+                        button {
+                            class: "text-xs text-blue-600 hover:underline whitespace-nowrap",
+                            title: "{hint}",
+                            onclick: move |_| {
+                                let color = color.clone();
+                                drag_context.update_theme(target.field(scheme), move |theme| theme.set_color(scheme, target, color));
+                            },
+                            "Use {color}"
+                        }
+                    }
+                }
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// "Same as primary" switch for colors that can follow the primary color.
+#[component]
+fn FollowPrimary(follows: bool, on_change: EventHandler<bool>) -> Element {
+    rsx! {
+        label { class: "flex items-center gap-2 mt-1 text-xs text-gray-600",
+            input {
+                r#type: "checkbox",
+                checked: follows,
+                onchange: move |e| on_change.call(e.checked()),
+            }
+            "Same as primary"
+        }
+    }
+}
+
+/// Picks a palette color by name and shade, or any hex, RGB or HSL color, which is stored as
+/// `#rrggbb` and exported as an arbitrary value such as `bg-[#1e40af]`.
+#[component]
+fn ColorSelector(label: String, current_value: String, on_change: EventHandler<String>) -> Element {
+    // Name and shade when it's a palette color
+    let palette_color: Option<(&'static str, &'static str)> = current_value.rsplit_once('-').and_then(|(name, shade)| {
+        let (name, _) = PALETTE.iter().find(|(n, _)| *n == name)?;
+        Some((*name, *SHADES.iter().find(|s| **s == shade)?))
+    });
+    let current_hex = color_hex(&current_value).unwrap_or_else(|| "#000000".into());
+    // The color the custom scale was generated from, so picking one of its shades keeps the scale
+    let mut brand_color = use_signal(|| current_hex.clone());
+    let mut typed = use_signal(|| None::<String>);
+
+    // Shades shown as swatches: the palette color's, or ones generated from the custom color
+    let swatches: Vec<(String, String)> = match palette_color {
+        Some((name, _)) => {
+            let (_, hexes) = PALETTE.iter().find(|(n, _)| *n == name).expect("checked above");
+            SHADES.iter().zip(hexes).map(|(shade, hex)| (format!("{name}-{shade}"), hex.to_string())).collect()
+        }
+        None => {
+            let scale = shade_scale(&brand_color())
+                .filter(|scale| scale.contains(&current_hex))
+                .or_else(|| shade_scale(&current_hex))
+                .unwrap_or_default();
+            scale.into_iter().map(|hex| (hex.clone(), hex)).collect()
+        }
+    };
+    let (current_name, current_shade) = palette_color.unwrap_or(("custom", "500"));
+    let current_shade = current_shade.to_string();
+    let text_value = typed().unwrap_or_else(|| current_value.clone());
+    let invalid = typed().is_some_and(|text| normalize_color(&text).is_none());
 
     rsx! {
         div {
@@ -195,41 +265,82 @@ fn ColorSelector(
                 // Color dropdown
                 select {
                     class: "flex-1 p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
-                    value: "{current_color}",
-                    onchange: move |e| {
-                        let new_color = e.value().clone();
-                        on_change.call(format!("{new_color}-{current_intensity_clone}"));
+                    onchange: {
+                        let current_hex = current_hex.clone();
+                        move |e: FormEvent| {
+                            typed.set(None);
+                            if e.value() == "custom" {
+                                brand_color.set(current_hex.clone());
+                                on_change.call(current_hex.clone());
+                            } else {
+                                on_change.call(format!("{}-{current_shade}", e.value()));
+                            }
+                        }
                     },
-                    for color in color_options {
-                        option {
-                            value: "{color}",
-                            selected: color == current_color,
-                            "{color}"
+                    for (name, _) in PALETTE {
+                        option { value: name, selected: name == current_name, "{name}" }
+                    }
+                    option { value: "custom", selected: current_name == "custom", "custom" }
+                }
+                // Shade dropdown
+                if current_name != "custom" {
+                    select {
+                        class: "flex-1 p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
+                        onchange: move |e| on_change.call(format!("{current_name}-{}", e.value())),
+                        for shade in SHADES {
+                            option { value: shade, selected: palette_color.is_some_and(|(_, s)| s == shade), "{shade}" }
                         }
                     }
                 }
-                // Intensity dropdown
-                select {
-                    class: "flex-1 p-2 border border-gray-300 rounded-lg focus:ring-blue-500 focus:border-blue-500",
-                    value: "{current_intensity}",
+                // Color picker
+                input {
+                    r#type: "color",
+                    class: "w-10 h-10 p-0.5 rounded border border-gray-300 cursor-pointer",
+                    value: "{current_hex}",
                     onchange: move |e| {
-                        let new_intensity = e.value().clone();
-                        on_change.call(format!("{current_color_clone}-{new_intensity}"));
+                        typed.set(None);
+                        brand_color.set(e.value());
+                        on_change.call(e.value());
                     },
-                    for intensity in intensity_options {
-                        option {
-                            value: "{intensity}",
-                            selected: intensity == current_intensity,
-                            "{intensity}"
+                }
+            }
+            // Hex, RGB or HSL value
+            input {
+                class: if invalid { "mt-2 w-full p-2 border border-red-400 rounded-lg font-mono text-xs focus:ring-red-500 focus:border-red-500" } else { "mt-2 w-full p-2 border border-gray-300 rounded-lg font-mono text-xs focus:ring-blue-500 focus:border-blue-500" },
+                placeholder: "#1e40af, rgb(30 64 175) or hsl(226 71% 40%)",
+                value: "{text_value}",
+                oninput: move |e| typed.set(Some(e.value())),
+                onchange: move |e| {
+                    if let Some(color) = normalize_color(&e.value()) {
+                        typed.set(None);
+                        if let Some(hex) = color_hex(&color) {
+                            brand_color.set(hex);
                         }
+                        on_change.call(color);
+                    }
+                },
+            }
+            if invalid {
+                p { class: "mt-1 text-xs text-red-600", "Not a color: use a hex, RGB or HSL value" }
+            }
+            // Shades
+            div { class: "flex mt-2 rounded overflow-hidden border border-gray-300",
+                for (value, hex) in swatches {
+                    button {
+                        key: "{value}",
+                        class: if value == current_value { "flex-1 h-6 ring-2 ring-inset ring-gray-900" } else { "flex-1 h-6 hover:opacity-80" },
+                        style: "background-color: {hex}",
+                        title: "{value}",
+                        onclick: {
+                            let value = value.clone();
+                            move |_| {
+                                typed.set(None);
+                                on_change.call(value.clone());
+                            }
+                        },
                     }
-                }
-                // Color preview
-                div {
-                    class: "w-10 h-10 rounded border border-gray-300",
-                    style: "background-color: var(--{current_color}-{current_intensity})",
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
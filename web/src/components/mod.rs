@@ -19,9 +19,46 @@ mod card;
 pub use card::CardGrid;
 
 mod drag_context;
-pub use drag_context::{DragDropContext, DragDropProvider, DraggableComponent, ComponentType, ComponentProperties, use_drag_drop_context};
+pub use drag_context::{Asset, Document, Page, DragDropContext, DragDropProvider, DraggableComponent, ComponentType, use_drag_drop_context};
+
+mod breakpoint;
+pub use breakpoint::{resolve_classes, Breakpoint, Device};
+
+mod grid;
+pub use grid::{GridSettings, GAPS, MAX_COLUMNS};
+
+mod export;
+pub use export::{ExportFormat, ExportOptions};
+
+mod export_dialog;
+pub use export_dialog::ExportDialog;
+
+mod history;
+
+mod palette;
+pub use palette::{color_hex, normalize_color, shade_scale, PALETTE, SHADES};
+
+mod theme;
+pub use theme::{ColorScheme, ContrastPair, ThemeSettings, ThemeToken, AA_CONTRAST, AAA_CONTRAST};
+
+mod persistence;
+pub use persistence::SaveStatus;
+
+mod project_file;
+pub use project_file::{file_stem, ProjectFile};
+
+mod download;
+pub use download::{copy_to_clipboard, download_file, download_files};
+
+mod upload;
+pub use upload::{format_size, read_uploads, ALLOWED_TYPES};
+#[cfg(feature = "server")]
+pub use upload::MAX_UPLOAD_BYTES;
 
 mod drop_zone;
-pub use drop_zone::DropZone;
+pub use drop_zone::{canvas_element_id, DropZone};
+
+mod inspector;
+pub use inspector::PropertiesInspector;
 
 pub mod left;
\ No newline at end of file
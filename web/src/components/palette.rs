@@ -0,0 +1,261 @@
+//! Tailwind's default color palette as hex values. The canvas can't rely on Tailwind to
+//! generate a class for every color a theme might pick, so it sets theme colors through CSS
+//! variables and needs their actual values. Colors outside the palette are kept as `#rrggbb`.
+
+/// Shades of every palette color, lightest first.
+pub const SHADES: [&str; 10] = ["50", "100", "200", "300", "400", "500", "600", "700", "800", "900"];
+
+/// Palette colors and their hex values, indexed like [`SHADES`].
+pub const PALETTE: [(&str, [&str; 10]); 22] = [
+    ("slate", ["#f8fafc", "#f1f5f9", "#e2e8f0", "#cbd5e1", "#94a3b8", "#64748b", "#475569", "#334155", "#1e293b", "#0f172a"]),
+    ("gray", ["#f9fafb", "#f3f4f6", "#e5e7eb", "#d1d5db", "#9ca3af", "#6b7280", "#4b5563", "#374151", "#1f2937", "#111827"]),
+    ("zinc", ["#fafafa", "#f4f4f5", "#e4e4e7", "#d4d4d8", "#a1a1aa", "#71717a", "#52525b", "#3f3f46", "#27272a", "#18181b"]),
+    ("neutral", ["#fafafa", "#f5f5f5", "#e5e5e5", "#d4d4d4", "#a3a3a3", "#737373", "#525252", "#404040", "#262626", "#171717"]),
+    ("stone", ["#fafaf9", "#f5f5f4", "#e7e5e4", "#d6d3d1", "#a8a29e", "#78716c", "#57534e", "#44403c", "#292524", "#1c1917"]),
+    ("red", ["#fef2f2", "#fee2e2", "#fecaca", "#fca5a5", "#f87171", "#ef4444", "#dc2626", "#b91c1c", "#991b1b", "#7f1d1d"]),
+    ("orange", ["#fff7ed", "#ffedd5", "#fed7aa", "#fdba74", "#fb923c", "#f97316", "#ea580c", "#c2410c", "#9a3412", "#7c2d12"]),
+    ("amber", ["#fffbeb", "#fef3c7", "#fde68a", "#fcd34d", "#fbbf24", "#f59e0b", "#d97706", "#b45309", "#92400e", "#78350f"]),
+    ("yellow", ["#fefce8", "#fef9c3", "#fef08a", "#fde047", "#facc15", "#eab308", "#ca8a04", "#a16207", "#854d0e", "#713f12"]),
+    ("lime", ["#f7fee7", "#ecfccb", "#d9f99d", "#bef264", "#a3e635", "#84cc16", "#65a30d", "#4d7c0f", "#3f6212", "#365314"]),
+    ("green", ["#f0fdf4", "#dcfce7", "#bbf7d0", "#86efac", "#4ade80", "#22c55e", "#16a34a", "#15803d", "#166534", "#14532d"]),
+    ("emerald", ["#ecfdf5", "#d1fae5", "#a7f3d0", "#6ee7b7", "#34d399", "#10b981", "#059669", "#047857", "#065f46", "#064e3b"]),
+    ("teal", ["#f0fdfa", "#ccfbf1", "#99f6e4", "#5eead4", "#2dd4bf", "#14b8a6", "#0d9488", "#0f766e", "#115e59", "#134e4a"]),
+    ("cyan", ["#ecfeff", "#cffafe", "#a5f3fc", "#67e8f9", "#22d3ee", "#06b6d4", "#0891b2", "#0e7490", "#155e75", "#164e63"]),
+    ("sky", ["#f0f9ff", "#e0f2fe", "#bae6fd", "#7dd3fc", "#38bdf8", "#0ea5e9", "#0284c7", "#0369a1", "#075985", "#0c4a6e"]),
+    ("blue", ["#eff6ff", "#dbeafe", "#bfdbfe", "#93c5fd", "#60a5fa", "#3b82f6", "#2563eb", "#1d4ed8", "#1e40af", "#1e3a8a"]),
+    ("indigo", ["#eef2ff", "#e0e7ff", "#c7d2fe", "#a5b4fc", "#818cf8", "#6366f1", "#4f46e5", "#4338ca", "#3730a3", "#312e81"]),
+    ("violet", ["#f5f3ff", "#ede9fe", "#ddd6fe", "#c4b5fd", "#a78bfa", "#8b5cf6", "#7c3aed", "#6d28d9", "#5b21b6", "#4c1d95"]),
+    ("purple", ["#faf5ff", "#f3e8ff", "#e9d5ff", "#d8b4fe", "#c084fc", "#a855f7", "#9333ea", "#7e22ce", "#6b21a8", "#581c87"]),
+    ("fuchsia", ["#fdf4ff", "#fae8ff", "#f5d0fe", "#f0abfc", "#e879f9", "#d946ef", "#c026d3", "#a21caf", "#86198f", "#701a75"]),
+    ("pink", ["#fdf2f8", "#fce7f3", "#fbcfe8", "#f9a8d4", "#f472b6", "#ec4899", "#db2777", "#be185d", "#9d174d", "#831843"]),
+    ("rose", ["#fff1f2", "#ffe4e6", "#fecdd3", "#fda4af", "#fb7185", "#f43f5e", "#e11d48", "#be123c", "#9f1239", "#881337"]),
+];
+
+/// Hex value of a palette color such as `blue-600`, `white` or `black`.
+pub fn palette_hex(color: &str) -> Option<&'static str> {
+    match color {
+        "white" => return Some("#ffffff"),
+        "black" => return Some("#000000"),
+        _ => {}
+    }
+    let (name, shade) = color.rsplit_once('-')?;
+    let (_, hexes) = PALETTE.iter().find(|(palette_name, _)| *palette_name == name)?;
+    let index = SHADES.iter().position(|s| *s == shade)?;
+    Some(hexes[index])
+}
+
+/// `color` as `#rrggbb`, whether it's a palette name or a hex, RGB or HSL value.
+pub fn color_hex(color: &str) -> Option<String> {
+    match palette_hex(color) {
+        Some(hex) => Some(hex.to_string()),
+        None => parse_rgb(color).map(to_hex),
+    }
+}
+
+/// A color the user typed, as the theme stores it: palette names as they are, hex, RGB and
+/// HSL values as `#rrggbb`. `None` if it isn't a color.
+pub fn normalize_color(input: &str) -> Option<String> {
+    let input = input.trim();
+    match palette_hex(input) {
+        Some(_) => Some(input.to_string()),
+        None => parse_rgb(input).map(to_hex),
+    }
+}
+
+/// Ten shades from 50 to 900 around `color`, which keeps its own place in the scale: the shade
+/// whose usual lightness is closest to its own. The others keep its hue and saturation.
+pub fn shade_scale(color: &str) -> Option<[String; 10]> {
+    const LIGHTNESS: [f32; 10] = [0.97, 0.94, 0.86, 0.77, 0.66, 0.56, 0.48, 0.40, 0.33, 0.26];
+
+    let hex = color_hex(color)?;
+    let (hue, saturation, lightness) = rgb_to_hsl(parse_rgb(&hex)?);
+    let anchor = (0..LIGHTNESS.len())
+        .min_by(|&a, &b| (LIGHTNESS[a] - lightness).abs().total_cmp(&(LIGHTNESS[b] - lightness).abs()))
+        .unwrap_or(5);
+    Some(std::array::from_fn(|index| {
+        if index == anchor {
+            return hex.clone();
+        }
+        // Stretch the usual lightness on either side of the anchor so it lands on `color`
+        let end = if index < anchor { LIGHTNESS[0] } else { LIGHTNESS[9] };
+        let shade_lightness = end + (LIGHTNESS[index] - end) * (lightness - end) / (LIGHTNESS[anchor] - end);
+        to_hex(hsl_to_rgb(hue, saturation, shade_lightness.clamp(0.0, 1.0)))
+    }))
+}
+
+/// WCAG contrast ratio between two colors, from 1 to 21.
+pub fn contrast_ratio(foreground: &str, background: &str) -> Option<f32> {
+    let (a, b) = (luminance(foreground)?, luminance(background)?);
+    Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+}
+
+/// The shade of `color`'s scale closest to it reaching `min_ratio` against `background`. Palette
+/// colors stay in their family (`gray-400` becomes `gray-500`), custom ones use [`shade_scale`].
+pub fn nearest_passing_shade(color: &str, background: &str, min_ratio: f32) -> Option<String> {
+    let family = color.rsplit_once('-').and_then(|(name, shade)| {
+        let (_, hexes) = PALETTE.iter().find(|(n, _)| *n == name)?;
+        Some((name, hexes, SHADES.iter().position(|s| *s == shade)?))
+    });
+    let (shades, current): (Vec<(String, String)>, usize) = match family {
+        Some((name, hexes, index)) => {
+            (SHADES.iter().zip(hexes).map(|(shade, hex)| (format!("{name}-{shade}"), hex.to_string())).collect(), index)
+        }
+        None => {
+            let hex = color_hex(color)?;
+            let scale = shade_scale(&hex)?;
+            let index = scale.iter().position(|shade| *shade == hex)?;
+            (scale.into_iter().map(|shade| (shade.clone(), shade)).collect(), index)
+        }
+    };
+    shades
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (_, hex))| contrast_ratio(hex, background).is_some_and(|ratio| ratio >= min_ratio))
+        .min_by_key(|(index, _)| index.abs_diff(current))
+        .map(|(_, (value, _))| value)
+}
+
+/// WCAG relative luminance, from 0 for black to 1 for white.
+fn luminance(color: &str) -> Option<f32> {
+    let (r, g, b) = parse_rgb(&color_hex(color)?)?;
+    let linear = |channel: u8| {
+        let value = channel as f32 / 255.0;
+        if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+/// Parses `#1e40af`, `#14a`, `rgb(30, 64, 175)` or `hsl(226deg 71% 40%)`. Alpha is ignored.
+fn parse_rgb(input: &str) -> Option<(u8, u8, u8)> {
+    let input = input.trim().to_ascii_lowercase();
+    if let Some(hex) = input.strip_prefix('#') {
+        let digits = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<Vec<u8>>>()?;
+        return match digits[..] {
+            [r, g, b] => Some((r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Some((r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            _ => None,
+        };
+    }
+
+    let (function, arguments) = input.strip_suffix(')')?.split_once('(')?;
+    let arguments: Vec<&str> = arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    if !(3..=4).contains(&arguments.len()) {
+        return None;
+    }
+    match function.trim() {
+        "rgb" | "rgba" => {
+            let channel = |argument: &str| match argument.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok().map(|p| p / 100.0 * 255.0),
+                None => argument.parse::<f32>().ok(),
+            }
+            .filter(|value| (0.0..=255.0).contains(value))
+            .map(|value| value.round() as u8);
+            Some((channel(arguments[0])?, channel(arguments[1])?, channel(arguments[2])?))
+        }
+        "hsl" | "hsla" => {
+            let hue = arguments[0].strip_suffix("deg").unwrap_or(arguments[0]).parse::<f32>().ok()?;
+            let percent = |argument: &str| {
+                argument.strip_suffix('%')?.parse::<f32>().ok().filter(|p| (0.0..=100.0).contains(p)).map(|p| p / 100.0)
+            };
+            Some(hsl_to_rgb(hue.rem_euclid(360.0), percent(arguments[1])?, percent(arguments[2])?))
+        }
+        _ => None,
+    }
+}
+
+fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Hue in degrees, saturation and lightness from 0 to 1.
+fn rgb_to_hsl((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match hue {
+        h if h < 60.0 => (chroma, x, 0.0),
+        h if h < 120.0 => (x, chroma, 0.0),
+        h if h < 180.0 => (0.0, chroma, x),
+        h if h < 240.0 => (0.0, x, chroma),
+        h if h < 300.0 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Class setting `property` (`bg`, `text`, `from`...) to `color`. Palette colors keep their
+/// name, `bg-blue-600`; anything else becomes an arbitrary value, `bg-[#1e40af]`.
+pub fn color_class(property: &str, color: &str) -> String {
+    if palette_hex(color).is_some() {
+        format!("{property}-{color}")
+    } else {
+        format!("{property}-[{color}]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_colors_become_hex() {
+        assert_eq!(normalize_color("blue-600").as_deref(), Some("blue-600"));
+        assert_eq!(normalize_color(" #1E40AF ").as_deref(), Some("#1e40af"));
+        assert_eq!(normalize_color("#14a").as_deref(), Some("#1144aa"));
+        assert_eq!(normalize_color("rgb(30, 64, 175)").as_deref(), Some("#1e40af"));
+        assert_eq!(normalize_color("rgb(30 64 175 / 50%)").as_deref(), Some("#1e40af"));
+        assert_eq!(normalize_color("hsl(0, 100%, 50%)").as_deref(), Some("#ff0000"));
+        assert_eq!(normalize_color("hsl(120deg 100% 25%)").as_deref(), Some("#008000"));
+        for invalid in ["blue", "#12345", "rgb(300, 0, 0)", "hsl(0, 100, 50)", "rgb(1, 2)"] {
+            assert_eq!(normalize_color(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn shade_scale_runs_light_to_dark_through_the_color() {
+        let scale = shade_scale("#1e40af").unwrap();
+        assert!(scale.contains(&"#1e40af".to_string()));
+        let lightness: Vec<f32> = scale.iter().map(|hex| rgb_to_hsl(parse_rgb(hex).unwrap()).2).collect();
+        assert!(lightness.windows(2).all(|pair| pair[0] > pair[1]), "{scale:?}");
+        assert_eq!(shade_scale("blue-600").unwrap()[5], "#2563eb");
+    }
+
+    #[test]
+    fn contrast_follows_wcag() {
+        assert!((contrast_ratio("white", "black").unwrap() - 21.0).abs() < 0.01);
+        let ratio = contrast_ratio("blue-600", "#fff").unwrap();
+        assert!((ratio - 5.17).abs() < 0.01, "{ratio}");
+
+        assert_eq!(nearest_passing_shade("gray-400", "white", 4.5).as_deref(), Some("gray-500"));
+        assert_eq!(nearest_passing_shade("gray-400", "white", 7.0).as_deref(), Some("gray-600"));
+        let custom = nearest_passing_shade("#93c5fd", "white", 4.5).unwrap();
+        assert!(contrast_ratio(&custom, "white").unwrap() >= 4.5, "{custom}");
+    }
+}
@@ -0,0 +1,130 @@
+//! Saves the builder document locally and restores it on startup: browser localStorage for the
+//! web build, a JSON file in the app data directory for the desktop build.
+
+use chrono::{DateTime, Local};
+use dioxus::prelude::*;
+
+use super::drag_context::{DragDropContext, Document};
+use super::project_file::ProjectFile;
+
+/// How long the document has to stay unchanged before it is written out.
+const AUTOSAVE_DELAY_MS: u32 = 500;
+
+/// Name the document is stored under unless the [`DragDropProvider`](super::DragDropProvider)
+/// is given another.
+pub const DEFAULT_STORAGE_KEY: &str = "document";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SaveStatus {
+    /// Nothing has changed since startup.
+    Unchanged,
+    Saved(DateTime<Local>),
+    Failed(String),
+}
+
+/// Loads the last document saved under `key`, if there is one and it can still be read.
+pub fn load_document(key: &str) -> Option<Document> {
+    let json = read_saved_document(key)?;
+    match ProjectFile::from_json(&json) {
+        Ok(project) => Some(project.into_document()),
+        Err(err) => {
+            log::error!("Ignoring unreadable saved document: {err}");
+            None
+        }
+    }
+}
+
+pub fn save_document(key: &str, document: &Document) -> Result<(), String> {
+    let json = ProjectFile::new(None, document).to_json()?;
+    write_saved_document(key, &json)
+}
+
+#[cfg(feature = "web")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(feature = "web")]
+fn read_saved_document(key: &str) -> Option<String> {
+    local_storage()?.get_item(&format!("dragndrop.{key}")).ok()?
+}
+
+#[cfg(feature = "web")]
+fn write_saved_document(key: &str, json: &str) -> Result<(), String> {
+    use web_sys::wasm_bindgen::JsCast;
+
+    local_storage()
+        .ok_or("localStorage is not available")?
+        .set_item(&format!("dragndrop.{key}"), json)
+        .map_err(|err| match err.dyn_ref::<web_sys::DomException>() {
+            Some(err) if err.name() == "QuotaExceededError" => {
+                "The browser's storage is full; save the project to the server or export it to keep your changes".to_string()
+            }
+            Some(err) => err.message(),
+            None => format!("{err:?}"),
+        })
+}
+
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+fn document_path(key: &str) -> Option<std::path::PathBuf> {
+    Some(dirs::data_dir()?.join("dragndrop").join(format!("{key}.json")))
+}
+
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+fn read_saved_document(key: &str) -> Option<String> {
+    std::fs::read_to_string(document_path(key)?).ok()
+}
+
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+fn write_saved_document(key: &str, json: &str) -> Result<(), String> {
+    let path = document_path(key).ok_or("No app data directory on this system")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(path, json).map_err(|err| err.to_string())
+}
+
+#[cfg(not(any(feature = "web", feature = "desktop")))]
+fn read_saved_document(_key: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(any(feature = "web", feature = "desktop")))]
+fn write_saved_document(_key: &str, _json: &str) -> Result<(), String> {
+    Err("This platform has no local storage".into())
+}
+
+#[cfg(feature = "web")]
+async fn sleep(ms: u32) {
+    gloo_timers::future::TimeoutFuture::new(ms).await;
+}
+
+#[cfg(all(feature = "desktop", not(feature = "web")))]
+async fn sleep(ms: u32) {
+    tokio::time::sleep(std::time::Duration::from_millis(ms.into())).await;
+}
+
+#[cfg(not(any(feature = "web", feature = "desktop")))]
+async fn sleep(_ms: u32) {}
+
+/// Writes the document out whenever it has been left alone for [`AUTOSAVE_DELAY_MS`].
+pub fn use_autosave(drag_context: DragDropContext) {
+    let mut pending_save = use_signal(|| None::<Task>);
+    let mut started = use_signal(|| false);
+
+    use_effect(move || {
+        // Reading the document here re-runs the effect on every change
+        let document = drag_context.document();
+        // The first run sees the document as it was loaded, which needs no saving
+        if !started.replace(true) {
+            return;
+        }
+        if let Some(task) = pending_save.take() {
+            task.cancel();
+        }
+        pending_save.set(Some(spawn(async move {
+            sleep(AUTOSAVE_DELAY_MS).await;
+            drag_context.save(&document);
+        })));
+    });
+}
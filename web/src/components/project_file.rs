@@ -0,0 +1,399 @@
+//! The versioned JSON format a project is saved, downloaded and opened in. The layout is
+//! documented in `PROJECT_FORMAT.md`; files from older versions are migrated on load.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::drag_context::{Asset, Document, Page};
+use super::theme::ThemeSettings;
+
+/// Value of the `format` field, so other JSON files are rejected up front.
+pub const FORMAT: &str = "dragndrop-project";
+
+pub const CURRENT_VERSION: u64 = 3;
+
+/// `MIGRATIONS[n]` turns a version `n` file into a version `n + 1` file.
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; CURRENT_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2, migrate_v2_to_v3];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectFile {
+    pub format: String,
+    pub version: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub next_id: usize,
+    pub pages: Vec<Page>,
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl ProjectFile {
+    pub fn new(name: Option<String>, document: &Document) -> Self {
+        Self {
+            format: FORMAT.into(),
+            version: CURRENT_VERSION,
+            name,
+            next_id: document.next_id,
+            pages: document.pages.clone(),
+            theme: document.theme.clone(),
+            assets: document.assets.clone(),
+        }
+    }
+
+    /// Parses a project file of any known version, migrating it to the current one.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut value: Value = serde_json::from_str(json).map_err(|err| format!("Not valid JSON: {err}"))?;
+
+        // Files written before the format was versioned have no `format` field at all
+        let mut version = match value.get("format") {
+            None => 0,
+            Some(format) if format == FORMAT => value
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or("The project file has no version")?,
+            Some(_) => return Err("This is not a DragNDrop project file".into()),
+        };
+        if version > CURRENT_VERSION {
+            return Err(format!(
+                "This project was saved by a newer version of the builder (format version {version})"
+            ));
+        }
+        while version < CURRENT_VERSION {
+            value = MIGRATIONS[version as usize](value)?;
+            version += 1;
+        }
+
+        serde_json::from_value(value).map_err(|err| format!("The project file is damaged: {err}"))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|err| err.to_string())
+    }
+
+    /// The project as a document with its first page open.
+    pub fn into_document(self) -> Document {
+        let mut document = Document {
+            next_id: self.next_id,
+            theme: self.theme,
+            assets: self.assets,
+            ..Document::default()
+        };
+        if !self.pages.is_empty() {
+            document.active_page = self.pages[0].id.clone();
+            document.pages = self.pages;
+        }
+        document
+    }
+}
+
+/// File name for a project: lowercase ASCII letters, digits and dashes only, so a name can never
+/// point outside the directory it is saved in. Empty when the name has no letters or digits.
+pub fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    stem.trim_end_matches('-').to_string()
+}
+
+/// A URL slug made from `base` that `taken` doesn't reject, e.g. `about-2`. Slugs name the
+/// exported files, so `index` is left to the home page and a leading digit gets a `page-`
+/// prefix (`2024.rs` is not a valid module name).
+pub fn unique_slug(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut base = file_stem(base);
+    if base.starts_with(|c: char| c.is_ascii_digit()) {
+        base = format!("page-{base}");
+    }
+    let taken = |slug: &str| slug == "index" || taken(slug);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| if base.is_empty() { format!("page-{n}") } else { format!("{base}-{n}") })
+        .find(|slug| !taken(slug))
+        .unwrap()
+}
+
+/// Version 0 is the bare `Document` the autosave wrote, optionally wrapped in the shared
+/// project store's `{ name, updated_at, document }` record.
+fn migrate_v0_to_v1(mut value: Value) -> Result<Value, String> {
+    let name = value.get("name").cloned();
+    if let Some(document) = value.get_mut("document") {
+        value = document.take();
+    }
+    let Value::Object(mut document) = value else {
+        return Err("The project file is damaged: expected an object".into());
+    };
+
+    let mut page = Map::new();
+    page.insert("name".into(), "Home".into());
+    page.insert("components".into(), document.remove("components").unwrap_or_default());
+
+    let mut project = Map::new();
+    project.insert("format".into(), FORMAT.into());
+    project.insert("version".into(), 1.into());
+    if let Some(name) = name.filter(Value::is_string) {
+        project.insert("name".into(), name);
+    }
+    // Some autosaves lack `next_id`; new ids then start above every id already in use
+    let next_id = match document.remove("next_id") {
+        Some(next_id @ Value::Number(_)) => next_id,
+        _ => next_free_id(page.get("components")).into(),
+    };
+    project.insert("next_id".into(), next_id);
+    project.insert("pages".into(), Value::Array(vec![Value::Object(page)]));
+    for key in ["theme", "assets"] {
+        if let Some(value) = document.remove(key) {
+            project.insert(key.into(), value);
+        }
+    }
+    Ok(Value::Object(project))
+}
+
+/// One more than the highest number ending a component id (`component_7`), or 1 if none do.
+fn next_free_id(components: Option<&Value>) -> u64 {
+    let Some(Value::Array(components)) = components else {
+        return 1;
+    };
+    components
+        .iter()
+        .filter_map(|component| component.get("id")?.as_str()?.rsplit('_').next()?.parse::<u64>().ok())
+        .max()
+        .map_or(1, |highest| highest + 1)
+}
+
+/// Version 2 gives pages an `id` and a URL `slug`. The first page becomes the home page.
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, String> {
+    let mut next_id = value.get("next_id").and_then(Value::as_u64).unwrap_or(1);
+    let Some(Value::Array(pages)) = value.get_mut("pages") else {
+        return Err("The project file is damaged: expected a list of pages".into());
+    };
+    let mut slugs: Vec<String> = Vec::new();
+    for (index, page) in pages.iter_mut().enumerate() {
+        let Value::Object(page) = page else {
+            return Err("The project file is damaged: expected a page object".into());
+        };
+        let slug = match page.get("name").and_then(Value::as_str) {
+            _ if index == 0 => String::new(),
+            name => unique_slug(name.unwrap_or_default(), |slug| slugs.iter().any(|taken| taken == slug)),
+        };
+        page.insert("id".into(), format!("page_{next_id}").into());
+        page.insert("slug".into(), slug.clone().into());
+        slugs.push(slug);
+        next_id += 1;
+    }
+    value["next_id"] = next_id.into();
+    value["version"] = 2.into();
+    Ok(value)
+}
+
+/// Version 3 replaces the `OneColumn` ... `FiveColumns` layouts with a single `Grid` type with
+/// `grid` settings. Their old `grid-cols-N gap-4` look is kept.
+///
+/// Layouts saved before columns had slots list all their children in `children`, and each
+/// child's column follows from its `position.0`, a percentage of the layout's width.
+fn migrate_v2_to_v3(mut value: Value) -> Result<Value, String> {
+    let Some(Value::Array(pages)) = value.get_mut("pages") else {
+        return Err("The project file is damaged: expected a list of pages".into());
+    };
+    for page in pages {
+        let Some(Value::Array(components)) = page.get_mut("components") else {
+            continue;
+        };
+        let offsets: Map<String, Value> = components
+            .iter()
+            .filter_map(|component| {
+                let id = component.get("id")?.as_str()?;
+                Some((id.to_string(), component.get("position")?.get(0)?.clone()))
+            })
+            .collect();
+        for component in components.iter_mut().filter_map(Value::as_object_mut) {
+            let columns = match component.get("component_type").and_then(Value::as_str) {
+                Some("OneColumn") => 1,
+                Some("TwoColumns") => 2,
+                Some("ThreeColumns") => 3,
+                Some("FourColumns") => 4,
+                Some("FiveColumns") => 5,
+                _ => continue,
+            };
+            // Without a track count the grid gives each column its own track, as before
+            let mut grid = Map::new();
+            grid.insert("gap".into(), 4.into());
+            let children = component.remove("children");
+            if !component.contains_key("columns") {
+                let mut slots = vec![Vec::new(); columns];
+                for child in children.as_ref().and_then(Value::as_array).into_iter().flatten() {
+                    let offset = child.as_str().and_then(|id| offsets.get(id)).and_then(Value::as_f64).unwrap_or(0.0);
+                    let column = (offset / (100.0 / columns as f64)).round().clamp(0.0, (columns - 1) as f64);
+                    slots[column as usize].push(child.clone());
+                }
+                let slots = slots.into_iter().map(|children| serde_json::json!({ "children": children })).collect();
+                component.insert("columns".into(), Value::Array(slots));
+            }
+            component.insert("component_type".into(), "Grid".into());
+            component.insert("grid".into(), Value::Object(grid));
+        }
+    }
+    value["version"] = 3.into();
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::ComponentType;
+
+    fn component(id: &str, component_type: &str, parent_id: Option<&str>) -> Value {
+        serde_json::json!({
+            "id": id,
+            "name": id,
+            "component_type": component_type,
+            "position": [0.0, 0.0],
+            "in_drop_zone": true,
+            "properties": { "styles": [], "content": null, "attributes": {} },
+            "parent_id": parent_id,
+        })
+    }
+
+    #[test]
+    fn autosaves_without_a_version_are_migrated() {
+        let v0 = serde_json::json!({
+            "name": "Shop",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "document": {
+                "components": [component("component_3", "Text", None), component("component_9", "Button", None)],
+                "theme": { "primary_color": "blue-600" },
+            },
+        });
+        let project = ProjectFile::from_json(&v0.to_string()).unwrap();
+        assert_eq!(project.version, CURRENT_VERSION);
+        assert_eq!(project.name.as_deref(), Some("Shop"));
+        // No `next_id`: counting goes on from the highest id, after the page took one
+        assert_eq!(project.pages[0].id, "page_10");
+        assert_eq!(project.next_id, 11);
+        assert_eq!(project.pages[0].name, "Home");
+        assert_eq!(project.pages[0].components.len(), 2);
+    }
+
+    #[test]
+    fn pages_gain_ids_and_slugs() {
+        let v1 = serde_json::json!({
+            "format": FORMAT,
+            "version": 1,
+            "next_id": 4,
+            "pages": [
+                { "name": "Home", "components": [component("component_1", "Header", None)] },
+                { "name": "About Us", "components": [] },
+            ],
+        });
+        let project = ProjectFile::from_json(&v1.to_string()).unwrap();
+        let pages: Vec<_> = project.pages.iter().map(|page| (page.id.as_str(), page.slug.as_str())).collect();
+        assert_eq!(pages, [("page_4", ""), ("page_5", "about-us")]);
+        assert_eq!(project.next_id, 6);
+    }
+
+    #[test]
+    fn column_layouts_become_grids() {
+        let v2 = serde_json::json!({
+            "format": FORMAT,
+            "version": 2,
+            "next_id": 3,
+            "pages": [{
+                "id": "page_1",
+                "name": "Home",
+                "slug": "",
+                "components": [component("component_2", "ThreeColumns", None), component("component_1", "Text", Some("component_2"))],
+            }],
+        });
+        // Layouts saved with column slots keep them as they are
+        let mut with_slots = v2.clone();
+        with_slots["pages"][0]["components"][0]["columns"] =
+            serde_json::json!([{ "children": [] }, { "children": [] }, { "children": ["component_1"] }]);
+
+        let project = ProjectFile::from_json(&v2.to_string()).unwrap();
+        let grid = &project.pages[0].components[0];
+        assert_eq!(grid.component_type, ComponentType::Grid);
+        assert_eq!(grid.columns.len(), 3);
+        assert_eq!(grid.grid_class(), "grid grid-cols-3 gap-4");
+        let document = ProjectFile::from_json(&with_slots.to_string()).unwrap().into_document();
+        assert_eq!(document.position_in_column("component_1"), Some(("component_2".into(), 2, 0)));
+    }
+
+    #[test]
+    fn column_layouts_keep_their_children_in_place() {
+        for (component_type, count) in [("OneColumn", 1), ("TwoColumns", 2), ("ThreeColumns", 3), ("FourColumns", 4), ("FiveColumns", 5)] {
+            // One child per column at the offset the old builder gave it, listed last to first
+            let mut layout = component("component_1", component_type, None);
+            let mut components = Vec::new();
+            let mut children = Vec::new();
+            for column in (0..count).rev() {
+                let id = format!("component_{}", column + 2);
+                let mut child = component(&id, "Text", Some("component_1"));
+                child["position"] = serde_json::json!([column as f64 * 100.0 / count as f64, 10.0]);
+                components.push(child);
+                children.push(id);
+            }
+            layout["children"] = serde_json::json!(children);
+            components.insert(0, layout);
+            let v2 = serde_json::json!({
+                "format": FORMAT,
+                "version": 2,
+                "next_id": count + 2,
+                "pages": [{ "id": "page_1", "name": "Home", "slug": "", "components": components }],
+            });
+
+            let document = ProjectFile::from_json(&v2.to_string()).unwrap().into_document();
+            let grid = document.get_component("component_1").unwrap();
+            assert_eq!(grid.grid_class(), format!("grid grid-cols-{count} gap-4"));
+            for column in 0..count {
+                let children = document.get_column_children("component_1", column);
+                let ids: Vec<_> = children.iter().map(|child| child.id.clone()).collect();
+                assert_eq!(ids, [format!("component_{}", column + 2)], "{component_type} column {column}");
+            }
+        }
+    }
+
+    #[test]
+    fn custom_and_unknown_types_survive_a_round_trip() {
+        let mut v3 = serde_json::json!({
+            "format": FORMAT,
+            "version": CURRENT_VERSION,
+            "next_id": 4,
+            "pages": [{
+                "id": "page_1",
+                "name": "Home",
+                "slug": "",
+                "components": [
+                    component("component_1", "Carousel", None),
+                    component("component_2", "Text", None),
+                    component("component_3", "Text", None),
+                ],
+            }],
+        });
+        v3["pages"][0]["components"][1]["component_type"] = serde_json::json!({ "Custom": "Pricing" });
+        v3["pages"][0]["components"][2]["component_type"] = serde_json::json!({ "Chart": { "kind": "bar" } });
+
+        let project = ProjectFile::from_json(&v3.to_string()).unwrap();
+        let types: Vec<_> = project.pages[0].components.iter().map(|c| c.component_type.clone()).collect();
+        assert_eq!(types[0], ComponentType::Custom("Carousel".into()));
+        assert_eq!(types[1], ComponentType::Custom("Pricing".into()));
+        assert_eq!(types[2].default_content(), "Chart");
+
+        let saved: Value = serde_json::from_str(&project.to_json().unwrap()).unwrap();
+        let saved_types: Vec<_> = saved["pages"][0]["components"].as_array().unwrap().iter().map(|c| c["component_type"].clone()).collect();
+        assert_eq!(
+            saved_types,
+            [
+                serde_json::json!({ "Custom": "Carousel" }),
+                serde_json::json!({ "Custom": "Pricing" }),
+                serde_json::json!({ "Chart": { "kind": "bar" } }),
+            ]
+        );
+    }
+}
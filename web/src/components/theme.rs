@@ -0,0 +1,351 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::palette::{color_class, color_hex, contrast_ratio, nearest_passing_shade, normalize_color};
+
+/// Project-wide colors, stored as Tailwind color names such as `blue-600`, or `#rrggbb` for
+/// colors outside the palette.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    #[serde(deserialize_with = "color")]
+    pub primary_color: String,
+    #[serde(deserialize_with = "color")]
+    pub secondary_color: String,
+    #[serde(deserialize_with = "color")]
+    pub accent_color: String,
+    /// Empty to follow the primary color.
+    #[serde(deserialize_with = "color")]
+    pub button_color: String,
+    /// Empty to follow the primary color.
+    #[serde(deserialize_with = "color")]
+    pub link_color: String,
+    #[serde(deserialize_with = "color")]
+    pub text_color: String,
+    #[serde(deserialize_with = "color")]
+    pub background_color: String,
+    pub custom_classes: String,
+    /// Colors used instead when the visitor's system is in dark mode.
+    pub dark: DarkPalette,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            primary_color: "blue-600".into(),
+            secondary_color: "gray-600".into(),
+            accent_color: "indigo-600".into(),
+            button_color: "".into(),
+            link_color: "".into(),
+            text_color: "gray-800".into(),
+            background_color: "white".into(),
+            custom_classes: "".into(),
+            dark: DarkPalette::default(),
+        }
+    }
+}
+
+/// The dark mode counterpart of the theme's colors. Its `button_color` and `link_color` follow
+/// its own `primary_color` when empty.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DarkPalette {
+    #[serde(deserialize_with = "color")]
+    pub primary_color: String,
+    #[serde(deserialize_with = "color")]
+    pub secondary_color: String,
+    #[serde(deserialize_with = "color")]
+    pub accent_color: String,
+    #[serde(deserialize_with = "color")]
+    pub button_color: String,
+    #[serde(deserialize_with = "color")]
+    pub link_color: String,
+    #[serde(deserialize_with = "color")]
+    pub text_color: String,
+    #[serde(deserialize_with = "color")]
+    pub background_color: String,
+}
+
+/// Reads a theme color the way the color picker stores it, and refuses anything else: the
+/// colors end up in class lists and style attributes, and project files come from anywhere.
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    if value.trim().is_empty() {
+        return Ok(String::new());
+    }
+    normalize_color(&value).ok_or_else(|| serde::de::Error::custom(format!("invalid theme color {value:?}")))
+}
+
+impl Default for DarkPalette {
+    fn default() -> Self {
+        Self {
+            primary_color: "blue-600".into(),
+            secondary_color: "gray-700".into(),
+            accent_color: "indigo-500".into(),
+            button_color: "".into(),
+            link_color: "blue-400".into(),
+            text_color: "gray-100".into(),
+            background_color: "gray-900".into(),
+        }
+    }
+}
+
+/// Which of the theme's palettes is in use.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    pub const ALL: [ColorScheme; 2] = [ColorScheme::Light, ColorScheme::Dark];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorScheme::Light => "Light",
+            ColorScheme::Dark => "Dark",
+        }
+    }
+}
+
+/// The theme colors components are styled with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThemeToken {
+    Primary,
+    Secondary,
+    Accent,
+    Button,
+    Link,
+    Text,
+    Background,
+}
+
+impl ThemeToken {
+    pub const ALL: [ThemeToken; 7] = [
+        ThemeToken::Primary,
+        ThemeToken::Secondary,
+        ThemeToken::Accent,
+        ThemeToken::Button,
+        ThemeToken::Link,
+        ThemeToken::Text,
+        ThemeToken::Background,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeToken::Primary => "primary",
+            ThemeToken::Secondary => "secondary",
+            ThemeToken::Accent => "accent",
+            ThemeToken::Button => "button",
+            ThemeToken::Link => "link",
+            ThemeToken::Text => "text",
+            ThemeToken::Background => "background",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemeToken::Primary => "Primary Color",
+            ThemeToken::Secondary => "Secondary Color",
+            ThemeToken::Accent => "Accent Color",
+            ThemeToken::Button => "Button Color",
+            ThemeToken::Link => "Link Color",
+            ThemeToken::Text => "Text Color",
+            ThemeToken::Background => "Background Color",
+        }
+    }
+
+    /// The `ThemeSettings` field holding the token's color in `scheme`.
+    pub fn field(&self, scheme: ColorScheme) -> &'static str {
+        let (light, dark) = match self {
+            ThemeToken::Primary => ("primary_color", "dark.primary_color"),
+            ThemeToken::Secondary => ("secondary_color", "dark.secondary_color"),
+            ThemeToken::Accent => ("accent_color", "dark.accent_color"),
+            ThemeToken::Button => ("button_color", "dark.button_color"),
+            ThemeToken::Link => ("link_color", "dark.link_color"),
+            ThemeToken::Text => ("text_color", "dark.text_color"),
+            ThemeToken::Background => ("background_color", "dark.background_color"),
+        };
+        match scheme {
+            ColorScheme::Light => light,
+            ColorScheme::Dark => dark,
+        }
+    }
+
+    /// CSS variable the canvas sets the token's color in, e.g. `--theme-primary`.
+    pub fn css_variable(&self) -> String {
+        format!("--theme-{}", self.name())
+    }
+}
+
+/// Minimum contrast ratio of normal-sized text for WCAG AA.
+pub const AA_CONTRAST: f32 = 4.5;
+/// Minimum contrast ratio of normal-sized text for WCAG AAA.
+pub const AAA_CONTRAST: f32 = 7.0;
+
+/// Text drawn over a background by the canvas and the exports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContrastPair {
+    pub label: &'static str,
+    /// `None` for white text.
+    pub foreground: Option<ThemeToken>,
+    pub background: ThemeToken,
+}
+
+impl ContrastPair {
+    pub const ALL: [ContrastPair; 7] = [
+        ContrastPair { label: "Text", foreground: Some(ThemeToken::Text), background: ThemeToken::Background },
+        ContrastPair { label: "Links", foreground: Some(ThemeToken::Link), background: ThemeToken::Background },
+        ContrastPair { label: "Card titles", foreground: Some(ThemeToken::Primary), background: ThemeToken::Background },
+        ContrastPair { label: "Button labels", foreground: None, background: ThemeToken::Button },
+        ContrastPair { label: "Hero text, primary side", foreground: None, background: ThemeToken::Primary },
+        ContrastPair { label: "Hero text, accent side", foreground: None, background: ThemeToken::Accent },
+        ContrastPair { label: "Footer text", foreground: None, background: ThemeToken::Secondary },
+    ];
+
+    /// The token to change to fix the pair: the text color, or the background under white text.
+    pub fn adjusted(&self) -> ThemeToken {
+        self.foreground.unwrap_or(self.background)
+    }
+}
+
+impl ThemeSettings {
+    /// The color behind `token`. Buttons and links follow the primary color until given one
+    /// of their own.
+    pub fn color(&self, token: ThemeToken) -> &str {
+        match token {
+            ThemeToken::Primary => &self.primary_color,
+            ThemeToken::Secondary => &self.secondary_color,
+            ThemeToken::Accent => &self.accent_color,
+            ThemeToken::Button if self.button_color.is_empty() => &self.primary_color,
+            ThemeToken::Button => &self.button_color,
+            ThemeToken::Link if self.link_color.is_empty() => &self.primary_color,
+            ThemeToken::Link => &self.link_color,
+            ThemeToken::Text => &self.text_color,
+            ThemeToken::Background => &self.background_color,
+        }
+    }
+
+    /// The token whose field holds `token`'s color: the primary for buttons and links
+    /// following it.
+    pub fn source(&self, token: ThemeToken) -> ThemeToken {
+        match token {
+            ThemeToken::Button if self.button_color.is_empty() => ThemeToken::Primary,
+            ThemeToken::Link if self.link_color.is_empty() => ThemeToken::Primary,
+            _ => token,
+        }
+    }
+
+    /// The theme with `scheme`'s colors in the light fields, so every method here reads them.
+    pub fn scheme(&self, scheme: ColorScheme) -> ThemeSettings {
+        match scheme {
+            ColorScheme::Light => self.clone(),
+            ColorScheme::Dark => ThemeSettings {
+                primary_color: self.dark.primary_color.clone(),
+                secondary_color: self.dark.secondary_color.clone(),
+                accent_color: self.dark.accent_color.clone(),
+                button_color: self.dark.button_color.clone(),
+                link_color: self.dark.link_color.clone(),
+                text_color: self.dark.text_color.clone(),
+                background_color: self.dark.background_color.clone(),
+                ..self.clone()
+            },
+        }
+    }
+
+    pub fn set_color(&mut self, scheme: ColorScheme, token: ThemeToken, color: String) {
+        let field = match (scheme, token) {
+            (ColorScheme::Light, ThemeToken::Primary) => &mut self.primary_color,
+            (ColorScheme::Light, ThemeToken::Secondary) => &mut self.secondary_color,
+            (ColorScheme::Light, ThemeToken::Accent) => &mut self.accent_color,
+            (ColorScheme::Light, ThemeToken::Button) => &mut self.button_color,
+            (ColorScheme::Light, ThemeToken::Link) => &mut self.link_color,
+            (ColorScheme::Light, ThemeToken::Text) => &mut self.text_color,
+            (ColorScheme::Light, ThemeToken::Background) => &mut self.background_color,
+            (ColorScheme::Dark, ThemeToken::Primary) => &mut self.dark.primary_color,
+            (ColorScheme::Dark, ThemeToken::Secondary) => &mut self.dark.secondary_color,
+            (ColorScheme::Dark, ThemeToken::Accent) => &mut self.dark.accent_color,
+            (ColorScheme::Dark, ThemeToken::Button) => &mut self.dark.button_color,
+            (ColorScheme::Dark, ThemeToken::Link) => &mut self.dark.link_color,
+            (ColorScheme::Dark, ThemeToken::Text) => &mut self.dark.text_color,
+            (ColorScheme::Dark, ThemeToken::Background) => &mut self.dark.background_color,
+        };
+        *field = color;
+    }
+
+    /// Contrast ratio of the pair's colors, `None` if one of them isn't a color.
+    pub fn contrast(&self, pair: ContrastPair) -> Option<f32> {
+        let foreground = pair.foreground.map_or("white", |token| self.color(token));
+        contrast_ratio(foreground, self.color(pair.background))
+    }
+
+    /// The shade of the pair's adjusted color closest to it that reaches `min_ratio`.
+    pub fn contrast_fix(&self, pair: ContrastPair, min_ratio: f32) -> Option<String> {
+        match pair.foreground {
+            Some(token) => nearest_passing_shade(self.color(token), self.color(pair.background), min_ratio),
+            None => nearest_passing_shade(self.color(pair.background), "white", min_ratio),
+        }
+    }
+
+    /// Class setting `property` to the token's color, e.g. `bg-blue-600` for `("bg", Button)`.
+    pub fn class(&self, property: &str, token: ThemeToken) -> String {
+        color_class(property, self.color(token))
+    }
+
+    /// The token's color as a CSS value, always `#rrggbb`: palette names become their hex value
+    /// and anything that isn't a color becomes black.
+    pub fn css_value(&self, token: ThemeToken) -> String {
+        color_hex(self.color(token)).unwrap_or_else(|| "#000000".into())
+    }
+
+    /// Every token as a CSS variable declaration, for a `style` attribute.
+    pub fn css_variables(&self) -> String {
+        ThemeToken::ALL
+            .iter()
+            .map(|&token| format!("{}: {};", token.css_variable(), self.css_value(token)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_fix_passes() {
+        let mut theme = ThemeSettings { primary_color: "sky-400".into(), ..ThemeSettings::default() };
+        let hero = ContrastPair::ALL[4];
+        assert!(theme.contrast(hero).unwrap() < AA_CONTRAST);
+
+        let fix = theme.contrast_fix(hero, AA_CONTRAST).unwrap();
+        assert_eq!(fix, "sky-700");
+        theme.set_color(ColorScheme::Light, hero.adjusted(), fix);
+        assert!(theme.contrast(hero).unwrap() >= AA_CONTRAST);
+    }
+
+    #[test]
+    fn dark_scheme_reads_the_dark_palette() {
+        let mut theme = ThemeSettings::default();
+        theme.set_color(ColorScheme::Dark, ThemeToken::Text, "slate-200".into());
+        theme.set_color(ColorScheme::Dark, ThemeToken::Link, "".into());
+
+        let dark = theme.scheme(ColorScheme::Dark);
+        assert_eq!(dark.color(ThemeToken::Text), "slate-200");
+        assert_eq!(dark.color(ThemeToken::Link), "blue-600");
+        assert_eq!(theme.color(ThemeToken::Text), "gray-800");
+    }
+
+    #[test]
+    fn loaded_colors_are_normalized_or_refused() {
+        let theme: ThemeSettings =
+            serde_json::from_str(r#"{"primary_color": "rgb(30 64 175)", "button_color": "", "dark": {"text_color": "slate-200"}}"#).unwrap();
+        assert_eq!(theme.primary_color, "#1e40af");
+        assert_eq!(theme.dark.text_color, "slate-200");
+        assert_eq!(theme.text_color, "gray-800");
+
+        for json in [r#"{"primary_color": "x\" onload=\"alert(1)"}"#, r#"{"dark": {"link_color": "red; background: url(x)"}}"#] {
+            assert!(serde_json::from_str::<ThemeSettings>(json).is_err(), "{json}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Checks files picked or dropped in the Assets panel: contents sniffed for their real type
+//! against the allow-list, and the size limit enforced before anything is read.
+
+use std::sync::Arc;
+
+use base64::Engine;
+use dioxus::html::FileEngine;
+
+use super::drag_context::Asset;
+
+/// Largest file the asset library takes.
+pub const MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// MIME types the asset library takes, as detected from the file's first bytes.
+pub const ALLOWED_TYPES: [&str; 5] = ["image/png", "image/jpeg", "image/gif", "image/svg+xml", "video/mp4"];
+
+/// A file that passed the checks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upload {
+    pub name: String,
+    pub mime_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+impl Upload {
+    /// An asset only this project holds. Its contents go in as a data URI, so it still shows
+    /// after the saved document is reloaded.
+    pub fn to_asset(&self) -> Asset {
+        Asset {
+            id: format!("asset_{}", uuid::Uuid::new_v4().simple()),
+            name: self.name.clone(),
+            file_type: self.mime_type.to_string(),
+            preview_url: self.data_uri(),
+            size: format_size(self.bytes.len() as u64),
+        }
+    }
+
+    /// The contents as a data URI.
+    pub fn data_uri(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&self.bytes);
+        format!("data:{};base64,{encoded}", self.mime_type)
+    }
+}
+
+/// Reads every file in `files`, one result per file in the order they were picked.
+pub async fn read_uploads(files: Arc<dyn FileEngine>) -> Vec<Result<Upload, String>> {
+    let mut uploads = Vec::new();
+    for name in files.files() {
+        uploads.push(read_upload(&*files, name).await);
+    }
+    uploads
+}
+
+async fn read_upload(files: &dyn FileEngine, name: String) -> Result<Upload, String> {
+    // Checked before reading, so an oversized file is never loaded into memory
+    if let Some(size) = files.file_size(&name).await {
+        if size > MAX_UPLOAD_BYTES {
+            return Err(format!("{name} is {}; files can be up to {}", format_size(size), format_size(MAX_UPLOAD_BYTES)));
+        }
+    }
+    let Some(bytes) = files.read_file(&name).await else {
+        return Err(format!("Could not read {name}"));
+    };
+    if bytes.len() as u64 > MAX_UPLOAD_BYTES {
+        return Err(format!("{name} is {}; files can be up to {}", format_size(bytes.len() as u64), format_size(MAX_UPLOAD_BYTES)));
+    }
+    match sniff_mime_type(&bytes) {
+        Some(mime_type) => Ok(Upload { name, mime_type, bytes }),
+        None => Err(format!("{name} is not a PNG, JPG, GIF, SVG or MP4 file")),
+    }
+}
+
+/// The allowed type `bytes` starts like, going by the content rather than the file name.
+pub fn sniff_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.get(4..8) == Some(b"ftyp") {
+        Some("video/mp4")
+    } else if is_svg(bytes) {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+/// SVG is text, so look for an `<svg` root after any XML declaration, doctype or comments.
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let mut rest = head.trim_start_matches('\u{feff}').trim_start();
+    while rest.starts_with("<?") || rest.starts_with("<!") {
+        let end = if rest.starts_with("<!--") { rest.find("-->").map(|i| i + 3) } else { rest.find('>').map(|i| i + 1) };
+        let Some(end) = end else {
+            return false;
+        };
+        rest = rest[end..].trim_start();
+    }
+    rest.starts_with("<svg")
+}
+
+/// `bytes` for people: `512 B`, `1.2 KB`, `10.0 MB`.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_are_detected_from_contents() {
+        assert_eq!(sniff_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(sniff_mime_type(&[0xff, 0xd8, 0xff, 0xe0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime_type(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(sniff_mime_type(b"<?xml version=\"1.0\"?>\n<!-- logo -->\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), Some("image/svg+xml"));
+        assert_eq!(sniff_mime_type(b"<html><svg/></html>"), None);
+        assert_eq!(sniff_mime_type(b"MZ\x90\0"), None);
+        assert_eq!(format_size(MAX_UPLOAD_BYTES), "10.0 MB");
+    }
+
+    #[test]
+    fn project_only_assets_carry_their_contents() {
+        let upload = Upload { name: "dot.gif".into(), mime_type: "image/gif", bytes: b"GIF89a".to_vec() };
+        let asset = upload.to_asset();
+        assert_eq!(asset.preview_url, "data:image/gif;base64,R0lGODlh");
+        assert_eq!(asset.size, "6 B");
+    }
+}
@@ -34,12 +34,56 @@ const FAVICON: Asset = asset!("/assets/favicon.ico");
 // The asset macro also minifies some assets like CSS and JS to make bundled smaller
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
+#[cfg(not(feature = "server"))]
 fn main() {
     // The `launch` function is the main entry point for a dioxus app. It takes a component and renders it with the platform feature
     // you have enabled
     dioxus::launch(App);
 }
 
+/// The server builds its own router so it can serve the asset library's files under `/library`
+/// next to the app and its server functions.
+#[cfg(feature = "server")]
+#[tokio::main]
+async fn main() {
+    if let Err(err) = serve().await {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "server")]
+async fn serve() -> Result<(), String> {
+    use axum::http::header::{HeaderValue, CONTENT_SECURITY_POLICY, X_CONTENT_TYPE_OPTIONS};
+    use tower_http::limit::RequestBodyLimitLayer;
+    use tower_http::set_header::SetResponseHeader;
+
+    let library_dir = components::left::library_dir();
+    std::fs::create_dir_all(&library_dir)
+        .map_err(|err| format!("Could not create the asset library at {}: {err}", library_dir.display()))?;
+    // Uploads share the app's origin, so a script inside an SVG must not run or be sniffed into HTML
+    let library = tower_http::services::ServeDir::new(library_dir);
+    let library = SetResponseHeader::overriding(library, CONTENT_SECURITY_POLICY, HeaderValue::from_static("sandbox"));
+    let library = SetResponseHeader::overriding(library, X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+
+    // Server functions read the whole body before decoding it, so cap it at a base64-encoded
+    // upload plus room for its name
+    let body_limit = (components::MAX_UPLOAD_BYTES as usize).div_ceil(3) * 4 + 64 * 1024;
+
+    let config = ServeConfig::new().map_err(|err| format!("Could not load the app to serve: {err}"))?;
+    let router = axum::Router::new()
+        .nest_service("/library", library)
+        .serve_dioxus_application(config, App)
+        .layer(RequestBodyLimitLayer::new(body_limit));
+    let address = dioxus::cli_config::fullstack_address_or_localhost();
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|err| format!("Could not listen on {address}: {err}"))?;
+    axum::serve(listener, router.into_make_service())
+        .await
+        .map_err(|err| format!("The server stopped: {err}"))
+}
+
 /// App is the main component of our app. Components are the building blocks of dioxus apps. Each component is a function
 /// that takes some props and returns an Element. In this case, App takes no props because it is the root of our app.
 ///
@@ -1,5 +1,5 @@
 use dioxus::prelude::*;
-use crate::components::DropZone;
+use crate::components::{DropZone, PropertiesInspector};
 
 #[component]
 pub fn DragDropDemo() -> Element {
@@ -10,7 +10,10 @@ pub fn DragDropDemo() -> Element {
                 p { class: "text-gray-600 mb-8", 
                     "Drag components from the left panel to build your layout. Use the blocks section in the left navigation."
                 }
-                DropZone {}
+                div { class: "flex gap-4 items-start",
+                    div { class: "flex-1 min-w-0", DropZone {} }
+                    PropertiesInspector {}
+                }
             }
         }
     }